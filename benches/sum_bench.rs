@@ -1,28 +1,7 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-
-pub fn sum(data: &[f32]) -> f32 {
-    data.iter().sum()
-}
-
-pub fn prepped_sum(data: &[f32]) -> f32 {
-    let mut acc0 = 0.0;
-    let mut acc1 = 0.0;
-    let mut acc2 = 0.0;
-    let mut acc3 = 0.0;
-    let chunks = data.chunks_exact(4);
-    let remainder = chunks.remainder();
-    for chunk in chunks {
-        acc0 += chunk[0];
-        acc1 += chunk[1];
-        acc2 += chunk[2];
-        acc3 += chunk[3];
-    }
-    let mut sum = acc0 + acc1 + acc2 + acc3;
-    for &x in remainder {
-        sum += x;
-    }
-    sum
-}
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ilp_data_prep::{
+    ilp_max, ilp_min, ilp_min_max, ilp_sum, ilp_sum_blocked, ilp_sum_exact, ilp_sum_n, native_sum,
+};
 
 #[inline(never)]
 pub fn divide(data: &[f32], divisor: f32) -> f32 {
@@ -55,10 +34,51 @@ fn criterion_benchmark(c: &mut Criterion) {
     let data = vec![1.1f32; size];
 
     let mut group_sum = c.benchmark_group("Summing elements of [f32]");
-    group_sum.bench_function("native sum", |b| b.iter(|| sum(black_box(&data))));
-    group_sum.bench_function("prepped sum", |b| b.iter(|| prepped_sum(black_box(&data))));
+    group_sum.throughput(Throughput::Bytes((size * 4) as u64));
+    group_sum.bench_function("native sum", |b| b.iter(|| native_sum(black_box(&data))));
+    group_sum.bench_function("prepped sum", |b| b.iter(|| ilp_sum(black_box(&data))));
     group_sum.finish();
 
+    let mut group_lanes = c.benchmark_group("Unroll width of ilp_sum_n");
+    group_lanes.throughput(Throughput::Elements(size as u64));
+    group_lanes.bench_with_input(BenchmarkId::new("lanes", 2), &data, |b, data| {
+        b.iter(|| ilp_sum_n::<2>(black_box(data)))
+    });
+    group_lanes.bench_with_input(BenchmarkId::new("lanes", 4), &data, |b, data| {
+        b.iter(|| ilp_sum_n::<4>(black_box(data)))
+    });
+    group_lanes.bench_with_input(BenchmarkId::new("lanes", 8), &data, |b, data| {
+        b.iter(|| ilp_sum_n::<8>(black_box(data)))
+    });
+    group_lanes.bench_with_input(BenchmarkId::new("lanes", 16), &data, |b, data| {
+        b.iter(|| ilp_sum_n::<16>(black_box(data)))
+    });
+    group_lanes.finish();
+
+    let mut group_blocked = c.benchmark_group("Block size of ilp_sum_blocked");
+    group_blocked.throughput(Throughput::Bytes((size * 4) as u64));
+    // 16 KiB and 256 KiB tiles, sized for typical L1d and L2 caches.
+    for block in [4096, 65536] {
+        group_blocked.bench_with_input(BenchmarkId::new("block", block), &data, |b, data| {
+            b.iter(|| ilp_sum_blocked(black_box(data), block))
+        });
+    }
+    group_blocked.finish();
+
+    let frame = vec![1.1f32; 64];
+
+    let mut group_exact = c.benchmark_group("Summing fixed 64-element frames");
+    group_exact.throughput(Throughput::Bytes((frame.len() * 4) as u64));
+    // The scalar four-lane kernel with its remainder loop, not the AVX2 path
+    // `ilp_sum` dispatches to, so only the remainder handling differs.
+    group_exact.bench_function("ilp_sum_n::<4>", |b| {
+        b.iter(|| ilp_sum_n::<4>(black_box(&frame)))
+    });
+    group_exact.bench_function("ilp_sum_exact", |b| {
+        b.iter(|| ilp_sum_exact(black_box(&frame)))
+    });
+    group_exact.finish();
+
     let size = 10_000_000;
     let data = vec![100.0f32; size];
     let divisor = black_box(7.0f32);
@@ -72,6 +92,20 @@ fn criterion_benchmark(c: &mut Criterion) {
     });
 
     group_div.finish();
+
+    let size = 40_000_000;
+    let data: Vec<f32> = (0..size).map(|i| (i % 1000) as f32).collect();
+
+    let mut group_range = c.benchmark_group("Min and max of [f32]");
+    group_range.throughput(Throughput::Bytes((size * 4) as u64));
+
+    group_range.bench_function("ilp_min + ilp_max", |b| {
+        b.iter(|| (ilp_min(black_box(&data)), ilp_max(black_box(&data))))
+    });
+
+    group_range.bench_function("ilp_min_max", |b| b.iter(|| ilp_min_max(black_box(&data))));
+
+    group_range.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,80 @@
+//! Complex-valued reductions, enabled by the `num-complex` feature.
+
+use num_complex::Complex32;
+
+use crate::error::{check_len, PrepError};
+
+/// Hermitian dot product `sum(a[i] * conj(b[i]))`.
+///
+/// Keeps four complex accumulators, with the real and imaginary parts in
+/// separate lanes, so eight independent chains are in flight per chunk. Two
+/// empty slices give zero.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` have different lengths; see [`try_ilp_cdot`].
+pub fn ilp_cdot(a: &[Complex32], b: &[Complex32]) -> Complex32 {
+    try_ilp_cdot(a, b).unwrap_or_else(|e| panic!("ilp_cdot: {e}"))
+}
+
+/// [`ilp_cdot`] returning [`PrepError::LengthMismatch`] instead of panicking.
+pub fn try_ilp_cdot(a: &[Complex32], b: &[Complex32]) -> Result<Complex32, PrepError> {
+    check_len(a.len(), b.len())?;
+    let mut re = [0.0f32; 4];
+    let mut im = [0.0f32; 4];
+    let a_chunks = a.chunks_exact(4);
+    let b_chunks = b.chunks_exact(4);
+    let a_rem = a_chunks.remainder();
+    let b_rem = b_chunks.remainder();
+    for (x, y) in a_chunks.zip(b_chunks) {
+        for lane in 0..4 {
+            // (xr + i xi) * (yr - i yi)
+            re[lane] += x[lane].re * y[lane].re + x[lane].im * y[lane].im;
+            im[lane] += x[lane].im * y[lane].re - x[lane].re * y[lane].im;
+        }
+    }
+    let mut sum = Complex32::new(
+        (re[0] + re[1]) + (re[2] + re[3]),
+        (im[0] + im[1]) + (im[2] + im[3]),
+    );
+    for (&x, &y) in a_rem.iter().zip(b_rem) {
+        sum += x * y.conj();
+    }
+    Ok(sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn ilp_cdot_matches_naive_reference() {
+        let a: Vec<Complex32> = (0..7)
+            .map(|i| Complex32::new(i as f32, 1.0 - i as f32))
+            .collect();
+        let b: Vec<Complex32> = (0..7)
+            .map(|i| Complex32::new(0.5 * i as f32, 2.0))
+            .collect();
+        let expected: Complex32 = a.iter().zip(&b).map(|(x, y)| x * y.conj()).sum();
+        assert_eq!(ilp_cdot(&a, &b), expected);
+    }
+
+    #[test]
+    fn ilp_cdot_conjugates_the_second_argument() {
+        let i = Complex32::new(0.0, 1.0);
+        // <i, i> = i * conj(i) = 1, not i * i = -1.
+        assert_eq!(ilp_cdot(&[i], &[i]), Complex32::new(1.0, 0.0));
+        assert_eq!(ilp_cdot(&[i; 4], &[i; 4]), Complex32::new(4.0, 0.0));
+        assert_eq!(
+            ilp_cdot(&[Complex32::new(1.0, 0.0)], &[i]),
+            Complex32::new(0.0, -1.0)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "equal length")]
+    fn ilp_cdot_rejects_length_mismatch() {
+        ilp_cdot(&[Complex32::new(1.0, 0.0)], &[]);
+    }
+}
@@ -0,0 +1,173 @@
+//! One configurable entry point over the dedicated sum kernels.
+
+use crate::error::PrepError;
+use crate::reduce::{ilp_nansum, ilp_nansum_kahan, ilp_sum, ilp_sum_kahan, ilp_sum_n};
+
+/// How [`ReducerConfig::reduce_sum`] sums a slice.
+///
+/// Start from [`ReducerConfig::new`] (automatic lanes, NaN propagates, plain
+/// accumulation) and chain the setters, e.g.
+/// `ReducerConfig::new().skip_nan(true).compensated(true)`.
+///
+/// `lanes` picks the unroll width of the plain sum only: NaN-skipping and
+/// compensated sums run on their four-lane kernels whatever it is set to.
+/// Until it is set, the plain sum is [`ilp_sum`], which may use a SIMD
+/// kernel; an explicit width always runs the scalar [`ilp_sum_n`] loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReducerConfig {
+    lanes: Option<usize>,
+    skip_nan: bool,
+    compensated: bool,
+}
+
+impl ReducerConfig {
+    /// Lane counts accepted by [`lanes`](Self::lanes).
+    pub const SUPPORTED_LANES: [usize; 5] = [1, 2, 4, 8, 16];
+
+    /// The configuration equivalent to [`ilp_sum`].
+    pub const fn new() -> Self {
+        Self {
+            lanes: None,
+            skip_nan: false,
+            compensated: false,
+        }
+    }
+
+    /// Sets the number of independent accumulators of the plain sum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lanes` is not one of [`SUPPORTED_LANES`](Self::SUPPORTED_LANES);
+    /// see [`try_lanes`](Self::try_lanes).
+    pub fn lanes(self, lanes: usize) -> Self {
+        self.try_lanes(lanes)
+            .unwrap_or_else(|e| panic!("ReducerConfig::lanes: {e}"))
+    }
+
+    /// [`lanes`](Self::lanes) returning [`PrepError::InvalidParam`] instead of
+    /// panicking.
+    pub fn try_lanes(self, lanes: usize) -> Result<Self, PrepError> {
+        if !Self::SUPPORTED_LANES.contains(&lanes) {
+            return Err(PrepError::InvalidParam("lanes must be 1, 2, 4, 8 or 16"));
+        }
+        Ok(Self {
+            lanes: Some(lanes),
+            ..self
+        })
+    }
+
+    /// Sets whether NaN elements are left out of the sum, as in
+    /// [`ilp_nansum`], instead of making it NaN.
+    pub const fn skip_nan(self, skip_nan: bool) -> Self {
+        Self { skip_nan, ..self }
+    }
+
+    /// Sets whether the sum is compensated, as in [`ilp_sum_kahan`].
+    pub const fn compensated(self, compensated: bool) -> Self {
+        Self {
+            compensated,
+            ..self
+        }
+    }
+
+    /// Sums `data` with the configured kernel. An empty slice sums to `0.0`.
+    pub fn reduce_sum(&self, data: &[f32]) -> f32 {
+        match (self.compensated, self.skip_nan) {
+            (true, true) => ilp_nansum_kahan(data),
+            (true, false) => ilp_sum_kahan(data),
+            (false, true) => ilp_nansum(data).0,
+            (false, false) => match self.lanes {
+                None => ilp_sum(data),
+                Some(1) => ilp_sum_n::<1>(data),
+                Some(2) => ilp_sum_n::<2>(data),
+                Some(4) => ilp_sum_n::<4>(data),
+                Some(8) => ilp_sum_n::<8>(data),
+                Some(16) => ilp_sum_n::<16>(data),
+                Some(_) => unreachable!("lanes is validated by try_lanes"),
+            },
+        }
+    }
+}
+
+impl Default for ReducerConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn data() -> Vec<f32> {
+        (0..203)
+            .map(|i| ((i * 37) % 101) as f32 * 0.7 - 30.0)
+            .collect()
+    }
+
+    #[test]
+    fn default_config_matches_ilp_sum() {
+        let data = data();
+        assert_eq!(ReducerConfig::default().reduce_sum(&data), ilp_sum(&data));
+    }
+
+    #[test]
+    fn lanes_pick_matching_unroll_width() {
+        let data = data();
+        let config = ReducerConfig::new();
+        assert_eq!(config.lanes(1).reduce_sum(&data), ilp_sum_n::<1>(&data));
+        assert_eq!(config.lanes(2).reduce_sum(&data), ilp_sum_n::<2>(&data));
+        assert_eq!(config.lanes(4).reduce_sum(&data), ilp_sum_n::<4>(&data));
+        assert_eq!(config.lanes(8).reduce_sum(&data), ilp_sum_n::<8>(&data));
+        assert_eq!(config.lanes(16).reduce_sum(&data), ilp_sum_n::<16>(&data));
+    }
+
+    #[test]
+    fn skip_nan_matches_ilp_nansum() {
+        let mut data = data();
+        data[3] = f32::NAN;
+        data[100] = f32::NAN;
+        let config = ReducerConfig::new().skip_nan(true);
+        assert_eq!(config.reduce_sum(&data), ilp_nansum(&data).0);
+    }
+
+    #[test]
+    fn compensated_matches_ilp_sum_kahan() {
+        let data = data();
+        let config = ReducerConfig::new().compensated(true).lanes(8);
+        assert_eq!(config.reduce_sum(&data), ilp_sum_kahan(&data));
+    }
+
+    #[test]
+    fn compensated_skip_nan_matches_kahan_of_filtered_input() {
+        let mut data = data();
+        data[0] = f32::NAN;
+        data[202] = f32::NAN;
+        let filtered: Vec<f32> = data
+            .iter()
+            .map(|&x| if x.is_nan() { 0.0 } else { x })
+            .collect();
+        let config = ReducerConfig::new().skip_nan(true).compensated(true);
+        assert_eq!(config.reduce_sum(&data), ilp_sum_kahan(&filtered));
+    }
+
+    #[test]
+    fn compensated_sums_stay_infinite() {
+        let plain = ReducerConfig::new().compensated(true);
+        let skip = plain.skip_nan(true);
+        let inf = f32::INFINITY;
+        assert_eq!(plain.reduce_sum(&[inf, 1.0, 2.0, 3.0, 4.0]), inf);
+        assert_eq!(plain.reduce_sum(&[f32::MAX, f32::MAX]), inf);
+        assert_eq!(skip.reduce_sum(&[f32::NAN, inf, 1.0, 2.0, 3.0]), inf);
+        assert_eq!(skip.reduce_sum(&[f32::MAX, f32::NAN, f32::MAX]), inf);
+    }
+
+    #[test]
+    fn try_lanes_rejects_unsupported_width() {
+        assert_eq!(
+            ReducerConfig::new().try_lanes(3),
+            Err(PrepError::InvalidParam("lanes must be 1, 2, 4, 8 or 16"))
+        );
+    }
+}
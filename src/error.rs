@@ -0,0 +1,63 @@
+//! Errors returned by the `try_` variants of the data-prep functions.
+
+use core::fmt;
+
+/// Why a data-prep call was rejected.
+///
+/// Every function that panics on bad input has a `try_` twin returning this
+/// instead; the panicking version is a thin wrapper that panics with the
+/// error's message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrepError {
+    /// Two inputs that are processed element by element differ in length.
+    LengthMismatch { a: usize, b: usize },
+    /// The input has no elements (or none usable) to compute a result from.
+    EmptyInput,
+    /// A parameter is outside its valid range; the message names it.
+    InvalidParam(&'static str),
+}
+
+impl fmt::Display for PrepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LengthMismatch { a, b } => {
+                write!(f, "slices must have equal length, got {a} and {b}")
+            }
+            Self::EmptyInput => f.write_str("input is empty"),
+            Self::InvalidParam(msg) => f.write_str(msg),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PrepError {}
+
+/// `Err(LengthMismatch)` unless `a == b`.
+#[inline(always)]
+pub(crate) fn check_len(a: usize, b: usize) -> Result<(), PrepError> {
+    if a == b {
+        Ok(())
+    } else {
+        Err(PrepError::LengthMismatch { a, b })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::ToString;
+
+    #[test]
+    fn prep_error_messages_name_the_problem() {
+        let err = PrepError::LengthMismatch { a: 1, b: 2 };
+        assert_eq!(
+            err.to_string(),
+            "slices must have equal length, got 1 and 2"
+        );
+        assert_eq!(PrepError::EmptyInput.to_string(), "input is empty");
+        assert_eq!(
+            PrepError::InvalidParam("stride must be non-zero").to_string(),
+            "stride must be non-zero"
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! Element types the reductions are generic over.
+
+/// Element type that [`ilp_sum`](crate::ilp_sum) can add up.
+///
+/// `combine` is the addition each lane accumulates with and `IDENTITY` its
+/// neutral element, the value every lane starts from and the sum of an
+/// empty slice. Implement it for your own types (fixed-point, wrappers,
+/// ...) to sum them without converting to a float. As with
+/// [`ilp_reduce`](crate::ilp_reduce), `combine` should be associative and
+/// commutative, or the result depends on the lane grouping.
+///
+/// Implemented for `f32`, `f64`, `i32` and `i64`.
+pub trait Accumulate: Copy {
+    /// Neutral element of [`combine`](Self::combine).
+    const IDENTITY: Self;
+
+    /// Adds two partial sums.
+    fn combine(self, other: Self) -> Self;
+
+    /// The kernel behind [`ilp_sum`](crate::ilp_sum). [`Sealed`] cannot be
+    /// named outside this crate, so other impls cannot override it.
+    #[doc(hidden)]
+    #[inline(always)]
+    fn sum_slice(data: &[Self], _: Sealed) -> Self {
+        crate::reduce::ilp_sum_scalar(data)
+    }
+}
+
+pub(crate) use private::Sealed;
+
+mod private {
+    /// Argument of the crate-private [`Accumulate`](super::Accumulate)
+    /// methods: public so it can appear in the trait, but unreachable.
+    pub struct Sealed(pub(crate) ());
+}
+
+/// Numeric element type accepted by [`ilp_sum_checked`](crate::ilp_sum_checked):
+/// an [`Accumulate`] whose addition can report overflow.
+///
+/// Implemented for `f32`, `f64`, `i32` and `i64`.
+pub trait IlpNum: Accumulate {
+    /// [`combine`](Accumulate::combine) that returns `None` on overflow. For
+    /// floats that means a finite pair of operands producing an infinite sum.
+    fn checked_add(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_float {
+    ($($t:ty => $sum:path),*) => {$(
+        impl Accumulate for $t {
+            const IDENTITY: Self = 0.0;
+
+            #[inline(always)]
+            fn combine(self, other: Self) -> Self {
+                self + other
+            }
+
+            #[inline(always)]
+            fn sum_slice(data: &[Self], _: Sealed) -> Self {
+                $sum(data)
+            }
+        }
+
+        impl IlpNum for $t {
+            #[inline(always)]
+            fn checked_add(self, other: Self) -> Option<Self> {
+                let sum = self + other;
+                if sum.is_infinite() && self.is_finite() && other.is_finite() {
+                    None
+                } else {
+                    Some(sum)
+                }
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_int {
+    ($($t:ty),*) => {$(
+        impl Accumulate for $t {
+            const IDENTITY: Self = 0;
+
+            #[inline(always)]
+            fn combine(self, other: Self) -> Self {
+                <$t>::wrapping_add(self, other)
+            }
+        }
+
+        impl IlpNum for $t {
+            #[inline(always)]
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+        }
+    )*};
+}
+
+impl_float!(f32 => crate::simd::sum_f32, f64 => crate::reduce::ilp_sum_scalar);
+impl_int!(i32, i64);
@@ -0,0 +1,65 @@
+//! Reductions over float slices that are unrolled into independent
+//! accumulators so the CPU can keep several additions in flight at once.
+//!
+//! The reductions only need `core`; disable the default `std` feature for
+//! `no_std` targets. Functions that need the standard library for float math,
+//! allocation or threads are only available with `std`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(all(test, not(feature = "std")))]
+#[macro_use]
+extern crate std;
+
+#[cfg(feature = "num-complex")]
+pub mod complex;
+pub mod config;
+pub mod error;
+pub mod float;
+#[cfg(feature = "std")]
+pub mod matrix;
+#[cfg(feature = "half")]
+pub mod mixed;
+#[cfg(feature = "rayon")]
+pub mod par;
+pub mod reduce;
+pub mod scan;
+mod simd;
+pub mod stats;
+pub mod stream;
+pub mod transform;
+
+#[cfg(feature = "num-complex")]
+pub use complex::{ilp_cdot, try_ilp_cdot};
+pub use config::ReducerConfig;
+pub use error::PrepError;
+pub use float::{Accumulate, IlpNum};
+#[cfg(feature = "std")]
+pub use matrix::{col_sums, row_sums, try_col_sums};
+#[cfg(feature = "half")]
+pub use mixed::{ilp_sum_bf16, ilp_sum_f16};
+#[cfg(feature = "rayon")]
+pub use par::{par_ilp_sum, par_ilp_sum_with_min_chunk, PAR_MIN_CHUNK};
+#[cfg(feature = "std")]
+pub use reduce::{cosine_similarity, ilp_l2_norm, try_cosine_similarity};
+pub use reduce::{
+    ilp_dot, ilp_max, ilp_mean, ilp_min, ilp_min_max, ilp_nansum, ilp_product, ilp_reduce, ilp_sum,
+    ilp_sum_aligned, ilp_sum_blocked, ilp_sum_checked, ilp_sum_deterministic, ilp_sum_exact,
+    ilp_sum_iter, ilp_sum_kahan, ilp_sum_n, ilp_sum_pairwise, ilp_sum_sq, ilp_sum_strided,
+    native_sum, try_ilp_dot, try_ilp_sum_blocked, try_ilp_sum_strided,
+};
+pub use scan::{ilp_prefix_sum, moving_average, try_ilp_prefix_sum, try_moving_average};
+pub use stats::{
+    approx_quantile, ilp_mean_variance, summarize, try_approx_quantile, Summary, QUANTILE_BINS,
+};
+#[cfg(feature = "std")]
+pub use stats::{geometric_mean, histogram, log_sum_exp, try_histogram};
+pub use stream::IlpSummer;
+#[cfg(feature = "std")]
+pub use transform::{
+    apply_standardization, ilp_axpy, softmax_in_place, standardize_in_place, try_ilp_axpy,
+};
+pub use transform::{
+    clip_and_count, clip_in_place, ilp_map_in_place, ilp_zip_map, impute_mean_in_place,
+    min_max_scale_in_place, try_clip_and_count, try_clip_in_place, try_ilp_zip_map,
+};
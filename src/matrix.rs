@@ -0,0 +1,91 @@
+//! Reductions over matrices stored as a slice of row slices.
+
+use crate::error::{check_len, PrepError};
+use crate::reduce::ilp_sum;
+
+/// Sum of each row, via [`ilp_sum`]. Rows may have different lengths; an
+/// empty row sums to `0.0`.
+pub fn row_sums(rows: &[&[f32]]) -> Vec<f32> {
+    rows.iter().map(|row| ilp_sum(row)).collect()
+}
+
+/// Sum of each of the `ncols` columns.
+///
+/// Rows are added one at a time into a vector of column accumulators, four
+/// columns per step, so every column keeps its own independent dependency
+/// chain and each row is read contiguously. With no rows every column sums to
+/// `0.0`.
+///
+/// # Panics
+///
+/// Panics if a row does not have exactly `ncols` elements; see
+/// [`try_col_sums`].
+pub fn col_sums(rows: &[&[f32]], ncols: usize) -> Vec<f32> {
+    try_col_sums(rows, ncols).unwrap_or_else(|e| panic!("col_sums: {e}"))
+}
+
+/// [`col_sums`] returning [`PrepError::LengthMismatch`] for the first row
+/// whose length is not `ncols`, instead of panicking.
+pub fn try_col_sums(rows: &[&[f32]], ncols: usize) -> Result<Vec<f32>, PrepError> {
+    for row in rows {
+        check_len(row.len(), ncols)?;
+    }
+    let mut sums = vec![0.0f32; ncols];
+    for row in rows {
+        let row_chunks = row.chunks_exact(4);
+        let row_rem = row_chunks.remainder();
+        let mut sum_chunks = sums.chunks_exact_mut(4);
+        for (xs, acc) in row_chunks.zip(&mut sum_chunks) {
+            acc[0] += xs[0];
+            acc[1] += xs[1];
+            acc[2] += xs[2];
+            acc[3] += xs[3];
+        }
+        for (acc, &x) in sum_chunks.into_remainder().iter_mut().zip(row_rem) {
+            *acc += x;
+        }
+    }
+    Ok(sums)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn row_and_col_sums_agree_on_total() {
+        let rows: [&[f32]; 2] = [&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]];
+        let by_row = row_sums(&rows);
+        let by_col = col_sums(&rows, 3);
+        assert_eq!(by_row, [6.0, 15.0]);
+        assert_eq!(by_col, [5.0, 7.0, 9.0]);
+        assert_eq!(ilp_sum(&by_row), ilp_sum(&by_col));
+    }
+
+    #[test]
+    fn row_sums_allows_ragged_rows() {
+        let rows: [&[f32]; 3] = [&[1.0; 7], &[], &[2.5]];
+        assert_eq!(row_sums(&rows), [7.0, 0.0, 2.5]);
+    }
+
+    #[test]
+    fn col_sums_matches_scalar_reference() {
+        let data: Vec<Vec<f32>> = (0..5)
+            .map(|r| (0..11).map(|c| (r * 11 + c) as f32 * 0.5).collect())
+            .collect();
+        let rows: Vec<&[f32]> = data.iter().map(Vec::as_slice).collect();
+        let expected: Vec<f32> = (0..11)
+            .map(|c| data.iter().map(|row| row[c]).sum())
+            .collect();
+        assert_eq!(col_sums(&rows, 11), expected);
+    }
+
+    #[test]
+    fn try_col_sums_reports_ragged_row() {
+        let rows: [&[f32]; 2] = [&[1.0, 2.0], &[3.0]];
+        assert_eq!(
+            try_col_sums(&rows, 2),
+            Err(PrepError::LengthMismatch { a: 1, b: 2 })
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! Half-precision inputs accumulated in `f32`, enabled by the `half` feature.
+
+use half::{bf16, f16};
+
+/// Sums `f16` values, accumulating in `f32`.
+///
+/// Each element is widened to `f32` before it is added to one of the four
+/// lane accumulators; no addition ever happens in `f16`, whose 11-bit
+/// mantissa would stop absorbing small terms once the running total reaches
+/// a few hundred. An empty slice sums to `0.0`.
+pub fn ilp_sum_f16(data: &[f16]) -> f32 {
+    widening_sum(data, f16::to_f32)
+}
+
+/// Sums `bf16` values, accumulating in `f32`. See [`ilp_sum_f16`].
+pub fn ilp_sum_bf16(data: &[bf16]) -> f32 {
+    widening_sum(data, bf16::to_f32)
+}
+
+#[inline(always)]
+fn widening_sum<T: Copy>(data: &[T], widen: impl Fn(T) -> f32) -> f32 {
+    let mut acc0 = 0.0;
+    let mut acc1 = 0.0;
+    let mut acc2 = 0.0;
+    let mut acc3 = 0.0;
+    let chunks = data.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        acc0 += widen(chunk[0]);
+        acc1 += widen(chunk[1]);
+        acc2 += widen(chunk[2]);
+        acc3 += widen(chunk[3]);
+    }
+    let mut sum = acc0 + acc1 + acc2 + acc3;
+    for &x in remainder {
+        sum += widen(x);
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ilp_sum_f16_accumulates_in_f32() {
+        let data = [f16::from_f32(0.1); 10_000];
+        let narrow = data.iter().fold(f16::ZERO, |acc, &x| acc + x);
+
+        let wide_err = (ilp_sum_f16(&data) - 1000.0).abs();
+        let narrow_err = (narrow.to_f32() - 1000.0).abs();
+        assert!(wide_err < 1.0, "f32 accumulation error {wide_err}");
+        assert!(narrow_err > 100.0, "f16 accumulation error {narrow_err}");
+    }
+
+    #[test]
+    fn ilp_sum_bf16_accumulates_in_f32() {
+        let data = [bf16::from_f32(1.5); 7];
+        assert_eq!(ilp_sum_bf16(&data), 10.5);
+    }
+}
@@ -0,0 +1,974 @@
+//! Slice reductions.
+
+use crate::error::{check_len, PrepError};
+use crate::float::{Accumulate, IlpNum, Sealed};
+
+/// Plain sequential sum: every addition depends on the previous one. An
+/// empty slice sums to `-0.0`, the starting value of `Iterator::sum` for
+/// floats, which compares equal to `0.0`.
+pub fn native_sum(data: &[f32]) -> f32 {
+    data.iter().sum()
+}
+
+/// Canonical, bit-reproducible sum: strictly left to right, one accumulator.
+///
+/// The order of the additions is fixed by this definition alone, and Rust
+/// never reassociates float arithmetic, so the result is identical on every
+/// architecture, lane width and opt level. Use it as the reference value when
+/// workers must agree bit for bit; it runs at the speed of one dependent
+/// addition per element. The accumulator starts at `-0.0`, like
+/// [`native_sum`], which it matches bit for bit, so an empty slice (or one of
+/// only negative zeros) sums to `-0.0`.
+pub fn ilp_sum_deterministic(data: &[f32]) -> f32 {
+    let mut sum = -0.0;
+    for &x in data {
+        sum += x;
+    }
+    sum
+}
+
+/// Sums `data` using four independent accumulators.
+///
+/// Splitting the loop-carried dependency lets the scheduler dispatch the
+/// additions of a chunk to different ports in the same cycle. The result can
+/// differ from [`native_sum`] in rounding because the additions are reordered.
+///
+/// For `f32` on x86_64 CPUs with AVX2 this runs a hand-vectorised kernel
+/// (four 8-wide accumulators) even when the crate is built without
+/// `target-cpu=native`; other types and targets use the scalar loop.
+///
+/// Integer sums wrap around on overflow, like `wrapping_add`, in release and
+/// debug builds alike; use [`ilp_sum_checked`] to detect it. An empty slice
+/// sums to `T::IDENTITY`, i.e. zero for the built-in types.
+///
+/// Any [`Accumulate`] type can be summed, including user-defined ones: the
+/// scalar loop is [`ilp_reduce`] with `T::IDENTITY` and `T::combine`. The
+/// `f32` kernel hangs off a hidden trait method that only this crate can
+/// override, so impls elsewhere always get the scalar loop.
+pub fn ilp_sum<T: Accumulate>(data: &[T]) -> T {
+    T::sum_slice(data, Sealed(()))
+}
+
+/// The portable four-accumulator kernel behind [`ilp_sum`].
+#[inline(always)]
+pub(crate) fn ilp_sum_scalar<T: Accumulate>(data: &[T]) -> T {
+    ilp_reduce(data, T::IDENTITY, T::combine)
+}
+
+/// Folds `data` with `combine` across four independent lane accumulators.
+///
+/// The engine behind the sum, product, min and max reductions. Element `i`
+/// of the chunked body goes to lane `i % 4`, each lane starting from
+/// `identity`; the lanes are then merged in order with the same `combine`,
+/// and the trailing `len % 4` elements are folded in last, in order.
+///
+/// `identity` must be neutral for `combine` and `combine` should be
+/// associative (and, since lanes interleave, commutative), or the result will
+/// depend on the lane grouping. An empty slice folds to `identity`. Float
+/// addition and multiplication are only approximately associative: the
+/// result is correct up to rounding, and the rounding differs from a
+/// sequential fold.
+#[inline]
+pub fn ilp_reduce<T: Copy>(data: &[T], identity: T, combine: impl Fn(T, T) -> T) -> T {
+    if InputLen::of(data) != InputLen::Normal {
+        // No full chunk: skip the lanes and fold the tail directly.
+        return data.iter().fold(identity, |acc, &x| combine(acc, x));
+    }
+    let mut acc0 = identity;
+    let mut acc1 = identity;
+    let mut acc2 = identity;
+    let mut acc3 = identity;
+    let chunks = data.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        acc0 = combine(acc0, chunk[0]);
+        acc1 = combine(acc1, chunk[1]);
+        acc2 = combine(acc2, chunk[2]);
+        acc3 = combine(acc3, chunk[3]);
+    }
+    let mut total = combine(combine(combine(acc0, acc1), acc2), acc3);
+    for &x in remainder {
+        total = combine(total, x);
+    }
+    total
+}
+
+/// [`ilp_sum`] that returns `None` if any addition overflows.
+///
+/// Every lane update and every lane merge is checked. Because the lanes add
+/// the elements in a different order than a sequential sum, an intermediate
+/// lane total can overflow even when the exact total would fit; the result
+/// is then `None` as well. For floats, overflow means a finite sum becoming
+/// infinite. An empty slice sums to `Some(T::IDENTITY)`.
+pub fn ilp_sum_checked<T: IlpNum>(data: &[T]) -> Option<T> {
+    let mut accs = [T::IDENTITY; 4];
+    let chunks = data.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        for (acc, &x) in accs.iter_mut().zip(chunk) {
+            *acc = acc.checked_add(x)?;
+        }
+    }
+    let mut sum = T::IDENTITY;
+    for &acc in &accs {
+        sum = sum.checked_add(acc)?;
+    }
+    for &x in remainder {
+        sum = sum.checked_add(x)?;
+    }
+    Some(sum)
+}
+
+/// [`ilp_sum`] with aligned SIMD loads for buffers of unknown alignment.
+///
+/// The slice is split with `align_to` into a scalar head up to the first
+/// 32-byte boundary, an aligned body of 8-element blocks summed with aligned
+/// AVX2 loads (or the scalar loop without AVX2), and a scalar tail. The
+/// result only differs from [`ilp_sum`] in rounding, as the grouping of the
+/// additions depends on where the boundary falls. An empty slice sums to
+/// `0.0`.
+pub fn ilp_sum_aligned(data: &[f32]) -> f32 {
+    crate::simd::sum_f32_aligned(data)
+}
+
+/// Sums `data` using `LANES` independent accumulators.
+///
+/// Wide cores keep more additions in flight with 8 or 16 lanes, older ones
+/// saturate at 2. `LANES = 1` degenerates to a plain sequential sum and
+/// `LANES = 0` is rejected at compile time. An empty slice sums to `0.0`.
+pub fn ilp_sum_n<const LANES: usize>(data: &[f32]) -> f32 {
+    lane_sum::<f32, LANES>(data)
+}
+
+/// [`ilp_sum`] for slices whose length is a multiple of 4, without the
+/// remainder loop.
+///
+/// Meant for fixed-size frames where the tail is known to be empty, so tiny
+/// inputs do not pay for the remainder check. An empty slice sums to `0.0`.
+///
+/// # Panics
+///
+/// Debug builds panic if `data.len()` is not a multiple of 4. Release builds
+/// do not check: the trailing `data.len() % 4` elements are silently left
+/// out of the sum, which is wrong.
+pub fn ilp_sum_exact(data: &[f32]) -> f32 {
+    debug_assert!(
+        data.len().is_multiple_of(4),
+        "ilp_sum_exact: length {} is not a multiple of 4",
+        data.len()
+    );
+    let mut acc0 = 0.0;
+    let mut acc1 = 0.0;
+    let mut acc2 = 0.0;
+    let mut acc3 = 0.0;
+    for chunk in data.chunks_exact(4) {
+        acc0 += chunk[0];
+        acc1 += chunk[1];
+        acc2 += chunk[2];
+        acc3 += chunk[3];
+    }
+    acc0 + acc1 + acc2 + acc3
+}
+
+/// Sums the items of `iter` by dealing them round-robin to four accumulators.
+///
+/// For sources that cannot hand out a contiguous slice. The items still feed
+/// four independent dependency chains, though without the memory-access
+/// pattern of the slice version. An empty iterator sums to `0.0`.
+pub fn ilp_sum_iter<I: Iterator<Item = f32>>(mut iter: I) -> f32 {
+    let mut acc0 = 0.0;
+    let mut acc1 = 0.0;
+    let mut acc2 = 0.0;
+    let mut acc3 = 0.0;
+    while let Some(x0) = iter.next() {
+        acc0 += x0;
+        let Some(x1) = iter.next() else { break };
+        acc1 += x1;
+        let Some(x2) = iter.next() else { break };
+        acc2 += x2;
+        let Some(x3) = iter.next() else { break };
+        acc3 += x3;
+    }
+    acc0 + acc1 + acc2 + acc3
+}
+
+/// Sums `data[offset]`, `data[offset + stride]`, `data[offset + 2 * stride]`, ...
+///
+/// With `offset = column` and `stride = ncols` this is the sum of one column
+/// of a row-major matrix. The picked elements are dealt across four
+/// accumulators as in [`ilp_sum_iter`]. Returns `0.0` if `offset` is past the
+/// end of `data`.
+///
+/// # Panics
+///
+/// Panics if `stride` is zero; see [`try_ilp_sum_strided`].
+pub fn ilp_sum_strided(data: &[f32], stride: usize, offset: usize) -> f32 {
+    try_ilp_sum_strided(data, stride, offset).unwrap_or_else(|e| panic!("ilp_sum_strided: {e}"))
+}
+
+/// [`ilp_sum_strided`] returning [`PrepError::InvalidParam`] for a zero
+/// `stride` instead of panicking.
+pub fn try_ilp_sum_strided(data: &[f32], stride: usize, offset: usize) -> Result<f32, PrepError> {
+    if stride == 0 {
+        return Err(PrepError::InvalidParam("stride must be non-zero"));
+    }
+    Ok(match data.get(offset..) {
+        Some(tail) => ilp_sum_iter(tail.iter().copied().step_by(stride)),
+        None => 0.0,
+    })
+}
+
+#[inline(always)]
+fn lane_sum<T: Accumulate, const LANES: usize>(data: &[T]) -> T {
+    const { assert!(LANES > 0, "LANES must be non-zero") };
+    let mut accs = [T::IDENTITY; LANES];
+    let chunks = data.chunks_exact(LANES);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        for (acc, &x) in accs.iter_mut().zip(chunk) {
+            *acc = acc.combine(x);
+        }
+    }
+    let mut sum = T::IDENTITY;
+    for &acc in &accs {
+        sum = sum.combine(acc);
+    }
+    for &x in remainder {
+        sum = sum.combine(x);
+    }
+    sum
+}
+
+/// Sums `data` with four compensated accumulators.
+///
+/// Each lane runs Neumaier's variant of Kahan summation, tracking the
+/// low-order bits lost by every addition in a separate compensation term. The
+/// lanes are then merged with the same compensated addition, and their
+/// compensation terms are carried into the merged total so nothing recovered
+/// inside a lane is dropped at the end. Roughly 4x the work of [`ilp_sum`] per
+/// element, but the error no longer grows with the input length. An empty
+/// slice sums to `0.0`; an infinite element or an overflowing sum gives an
+/// infinite result, as with [`ilp_sum`], not NaN.
+pub fn ilp_sum_kahan(data: &[f32]) -> f32 {
+    kahan_sum_by(data, |x| x)
+}
+
+/// [`ilp_sum_kahan`] that skips NaN elements, for
+/// [`ReducerConfig`](crate::ReducerConfig).
+pub(crate) fn ilp_nansum_kahan(data: &[f32]) -> f32 {
+    // Adding zero leaves both the sum and the compensation term unchanged.
+    kahan_sum_by(data, |x| if x.is_nan() { 0.0 } else { x })
+}
+
+#[inline(always)]
+fn kahan_sum_by(data: &[f32], f: impl Fn(f32) -> f32) -> f32 {
+    let mut accs = [Compensated::ZERO; 4];
+    let chunks = data.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        accs[0].add(f(chunk[0]));
+        accs[1].add(f(chunk[1]));
+        accs[2].add(f(chunk[2]));
+        accs[3].add(f(chunk[3]));
+    }
+    let mut total = accs[0];
+    for &acc in &accs[1..] {
+        total.merge(acc);
+    }
+    for &x in remainder {
+        total.add(f(x));
+    }
+    total.value()
+}
+
+/// Leaf size for [`ilp_sum_pairwise`]; each leaf is summed with [`ilp_sum`].
+const PAIRWISE_BLOCK: usize = 128;
+
+/// Sums `data` by pairwise (cascade) summation.
+///
+/// The slice is cut into blocks of 128 elements, each summed with the
+/// four-accumulator loop, and the block sums are combined as a balanced
+/// binary tree. Rounding error grows as O(log n) instead of the O(n) of a
+/// single running accumulator, at close to the speed of [`ilp_sum`].
+///
+/// The tree is built bottom-up with a fixed-size stack of partial sums, one
+/// slot per level, so there is no recursion and no allocation regardless of
+/// the input length. An empty slice sums to `0.0`.
+pub fn ilp_sum_pairwise(data: &[f32]) -> f32 {
+    let mut stack = [0.0f32; usize::BITS as usize];
+    let mut depth = 0;
+    for (i, block) in data.chunks(PAIRWISE_BLOCK).enumerate() {
+        let mut sum = ilp_sum(block);
+        // The stack mirrors the binary representation of the number of
+        // blocks seen so far: merging on each trailing one bit of `i` keeps
+        // every stored partial the sum of a power-of-two run of blocks.
+        let mut n = i;
+        while n & 1 == 1 {
+            depth -= 1;
+            sum += stack[depth];
+            n >>= 1;
+        }
+        stack[depth] = sum;
+        depth += 1;
+    }
+    let mut total = 0.0;
+    while depth > 0 {
+        depth -= 1;
+        total += stack[depth];
+    }
+    total
+}
+
+/// Sums `data` in tiles of `block` elements, each summed with [`ilp_sum`],
+/// and adds the tile partials in order.
+///
+/// Lets callers size the tiles to their L1 or L2 cache, e.g. when the sum is
+/// interleaved with other work on the same tile. The result only differs from
+/// [`ilp_sum`] in rounding, as the additions are grouped by tile. An empty
+/// slice sums to `0.0`.
+///
+/// # Panics
+///
+/// Panics if `block` is zero; see [`try_ilp_sum_blocked`].
+pub fn ilp_sum_blocked(data: &[f32], block: usize) -> f32 {
+    try_ilp_sum_blocked(data, block).unwrap_or_else(|e| panic!("ilp_sum_blocked: {e}"))
+}
+
+/// [`ilp_sum_blocked`] returning [`PrepError::InvalidParam`] instead of
+/// panicking.
+pub fn try_ilp_sum_blocked(data: &[f32], block: usize) -> Result<f32, PrepError> {
+    if block == 0 {
+        return Err(PrepError::InvalidParam("block must be non-zero"));
+    }
+    let mut total = 0.0;
+    for tile in data.chunks(block) {
+        total += ilp_sum(tile);
+    }
+    Ok(total)
+}
+
+/// Dot product of `a` and `b` using four independent accumulators. Two empty
+/// slices have a dot product of `0.0`.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` have different lengths; see [`try_ilp_dot`].
+pub fn ilp_dot(a: &[f32], b: &[f32]) -> f32 {
+    try_ilp_dot(a, b).unwrap_or_else(|e| panic!("ilp_dot: {e}"))
+}
+
+/// [`ilp_dot`] returning [`PrepError::LengthMismatch`] instead of panicking.
+pub fn try_ilp_dot(a: &[f32], b: &[f32]) -> Result<f32, PrepError> {
+    check_len(a.len(), b.len())?;
+    let mut acc0 = 0.0;
+    let mut acc1 = 0.0;
+    let mut acc2 = 0.0;
+    let mut acc3 = 0.0;
+    let a_chunks = a.chunks_exact(4);
+    let b_chunks = b.chunks_exact(4);
+    let a_rem = a_chunks.remainder();
+    let b_rem = b_chunks.remainder();
+    for (x, y) in a_chunks.zip(b_chunks) {
+        acc0 += x[0] * y[0];
+        acc1 += x[1] * y[1];
+        acc2 += x[2] * y[2];
+        acc3 += x[3] * y[3];
+    }
+    let mut sum = acc0 + acc1 + acc2 + acc3;
+    for (&x, &y) in a_rem.iter().zip(b_rem) {
+        sum += x * y;
+    }
+    Ok(sum)
+}
+
+/// Sum of the non-NaN elements of `data`, with the number of elements that
+/// contributed.
+///
+/// Missing values encoded as NaN are skipped inside each of the four lanes, so
+/// `sum / count as f32` is the NaN-aware mean. Infinities are not missing
+/// values: they are summed and counted like any other number. Empty input
+/// gives `(0.0, 0)`.
+pub fn ilp_nansum(data: &[f32]) -> (f32, usize) {
+    let mut sums = [0.0f32; 4];
+    let mut counts = [0usize; 4];
+    let chunks = data.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        for ((sum, count), &x) in sums.iter_mut().zip(&mut counts).zip(chunk) {
+            let valid = !x.is_nan();
+            *sum += if valid { x } else { 0.0 };
+            *count += valid as usize;
+        }
+    }
+    let mut sum = sums[0] + sums[1] + sums[2] + sums[3];
+    let mut count = counts[0] + counts[1] + counts[2] + counts[3];
+    for &x in remainder {
+        if !x.is_nan() {
+            sum += x;
+            count += 1;
+        }
+    }
+    (sum, count)
+}
+
+/// Sum of squares of `data` using four independent accumulators.
+///
+/// The squares are accumulated unscaled, so the result overflows to `inf`
+/// once it exceeds `f32::MAX` (about `3.4e38`), which already happens for a
+/// single element above `sqrt(f32::MAX)` (about `1.8e19`). Inputs that large
+/// should be rescaled first; a scaled, LAPACK-`nrm2`-style variant would
+/// avoid this at the cost of a division per element. An empty slice gives
+/// `0.0`.
+pub fn ilp_sum_sq(data: &[f32]) -> f32 {
+    let mut acc0 = 0.0;
+    let mut acc1 = 0.0;
+    let mut acc2 = 0.0;
+    let mut acc3 = 0.0;
+    let chunks = data.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        acc0 += chunk[0] * chunk[0];
+        acc1 += chunk[1] * chunk[1];
+        acc2 += chunk[2] * chunk[2];
+        acc3 += chunk[3] * chunk[3];
+    }
+    let mut sum = acc0 + acc1 + acc2 + acc3;
+    for &x in remainder {
+        sum += x * x;
+    }
+    sum
+}
+
+/// Euclidean norm of `data`, the square root of [`ilp_sum_sq`].
+///
+/// Shares its overflow behaviour: the norm is `inf` whenever the sum of
+/// squares overflows, even if the norm itself would fit in an `f32`. The
+/// norm of an empty slice is `0.0`.
+#[cfg(feature = "std")]
+pub fn ilp_l2_norm(data: &[f32]) -> f32 {
+    ilp_sum_sq(data).sqrt()
+}
+
+/// Cosine similarity of `a` and `b`, `dot(a, b) / (|a| * |b|)`.
+///
+/// The dot product and both sums of squares are accumulated in the same
+/// four-lane pass, so each input is read once rather than three times as
+/// with separate [`ilp_dot`] and [`ilp_sum_sq`] calls. The three sums are
+/// kept in `f64`, where the product of two finite `f32` values neither
+/// overflows nor underflows, so unlike [`ilp_sum_sq`] elements above
+/// `sqrt(f32::MAX)` are fine. The result is clamped to `[-1, 1]` to absorb
+/// rounding. Returns `None` if either vector has zero norm (including empty
+/// input), where the angle is undefined. A NaN or infinite element makes
+/// the result `Some(NaN)`.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` have different lengths; see
+/// [`try_cosine_similarity`].
+#[cfg(feature = "std")]
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    try_cosine_similarity(a, b).unwrap_or_else(|e| panic!("cosine_similarity: {e}"))
+}
+
+/// [`cosine_similarity`] returning [`PrepError::LengthMismatch`] instead of
+/// panicking.
+#[cfg(feature = "std")]
+pub fn try_cosine_similarity(a: &[f32], b: &[f32]) -> Result<Option<f32>, PrepError> {
+    check_len(a.len(), b.len())?;
+    let mut dots = [0.0f64; 4];
+    let mut aas = [0.0f64; 4];
+    let mut bbs = [0.0f64; 4];
+    let a_chunks = a.chunks_exact(4);
+    let b_chunks = b.chunks_exact(4);
+    let a_rem = a_chunks.remainder();
+    let b_rem = b_chunks.remainder();
+    for (x, y) in a_chunks.zip(b_chunks) {
+        for lane in 0..4 {
+            let (x, y) = (f64::from(x[lane]), f64::from(y[lane]));
+            dots[lane] += x * y;
+            aas[lane] += x * x;
+            bbs[lane] += y * y;
+        }
+    }
+    let mut dot = dots[0] + dots[1] + dots[2] + dots[3];
+    let mut aa = aas[0] + aas[1] + aas[2] + aas[3];
+    let mut bb = bbs[0] + bbs[1] + bbs[2] + bbs[3];
+    for (&x, &y) in a_rem.iter().zip(b_rem) {
+        let (x, y) = (f64::from(x), f64::from(y));
+        dot += x * y;
+        aa += x * x;
+        bb += y * y;
+    }
+    if aa == 0.0 || bb == 0.0 {
+        return Ok(None);
+    }
+    let cos = dot / (aa.sqrt() * bb.sqrt());
+    Ok(Some((cos as f32).clamp(-1.0, 1.0)))
+}
+
+/// Arithmetic mean of `data`, [`ilp_sum`] divided by the length. Returns
+/// `None` for empty input.
+pub fn ilp_mean(data: &[f32]) -> Option<f32> {
+    match InputLen::of(data) {
+        InputLen::Empty => None,
+        _ => Some(ilp_sum(data) / data.len() as f32),
+    }
+}
+
+/// Product of `data` using four independent multiplicative accumulators.
+///
+/// Returns `1.0` for empty input. Long products overflow or underflow
+/// quickly in `f32`; `geometric_mean` (available with `std`) is a log-space
+/// alternative.
+pub fn ilp_product(data: &[f32]) -> f32 {
+    ilp_reduce(data, 1.0, |a, b| a * b)
+}
+
+/// Smallest element of `data`, tracked in four independent lanes.
+///
+/// NaNs are ignored: the result is the minimum of the non-NaN elements, and
+/// `Some(NaN)` only if every element is NaN. Returns `None` for empty input.
+pub fn ilp_min(data: &[f32]) -> Option<f32> {
+    lane_extreme(data, f32::min)
+}
+
+/// Largest element of `data`, tracked in four independent lanes.
+///
+/// NaNs are ignored: the result is the maximum of the non-NaN elements, and
+/// `Some(NaN)` only if every element is NaN. Returns `None` for empty input.
+pub fn ilp_max(data: &[f32]) -> Option<f32> {
+    lane_extreme(data, f32::max)
+}
+
+/// Smallest and largest element of `data` in a single pass.
+///
+/// Keeps four running minima and four running maxima, so the data is read
+/// once instead of twice as with [`ilp_min`] followed by [`ilp_max`]. NaNs
+/// are ignored the same way. Returns `None` for empty input.
+pub fn ilp_min_max(data: &[f32]) -> Option<(f32, f32)> {
+    if InputLen::of(data) == InputLen::Empty {
+        return None;
+    }
+    let mut mins = [f32::NAN; 4];
+    let mut maxs = [f32::NAN; 4];
+    let chunks = data.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        mins[0] = mins[0].min(chunk[0]);
+        mins[1] = mins[1].min(chunk[1]);
+        mins[2] = mins[2].min(chunk[2]);
+        mins[3] = mins[3].min(chunk[3]);
+        maxs[0] = maxs[0].max(chunk[0]);
+        maxs[1] = maxs[1].max(chunk[1]);
+        maxs[2] = maxs[2].max(chunk[2]);
+        maxs[3] = maxs[3].max(chunk[3]);
+    }
+    let mut min = mins[0].min(mins[1]).min(mins[2].min(mins[3]));
+    let mut max = maxs[0].max(maxs[1]).max(maxs[2].max(maxs[3]));
+    for &x in remainder {
+        min = min.min(x);
+        max = max.max(x);
+    }
+    Some((min, max))
+}
+
+// `f32::min`/`f32::max` return the other operand when one is NaN, so NaN
+// acts as the identity and the first real value of each lane wins.
+#[inline(always)]
+fn lane_extreme(data: &[f32], pick: impl Fn(f32, f32) -> f32) -> Option<f32> {
+    match InputLen::of(data) {
+        InputLen::Empty => None,
+        _ => Some(ilp_reduce(data, f32::NAN, pick)),
+    }
+}
+
+/// How much of the four-lane structure an input fills, so reductions can
+/// pick their empty result and short inputs can skip the lane setup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum InputLen {
+    /// No elements; the reduction returns its empty result.
+    Empty,
+    /// One to three elements: no full chunk, only the tail.
+    Short,
+    /// At least one full four-element chunk.
+    Normal,
+}
+
+impl InputLen {
+    #[inline(always)]
+    pub(crate) fn of<T>(data: &[T]) -> Self {
+        match data.len() {
+            0 => Self::Empty,
+            1..=3 => Self::Short,
+            _ => Self::Normal,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Compensated {
+    sum: f32,
+    comp: f32,
+}
+
+impl Compensated {
+    const ZERO: Self = Self {
+        sum: 0.0,
+        comp: 0.0,
+    };
+
+    #[inline(always)]
+    fn add(&mut self, x: f32) {
+        let t = self.sum + x;
+        // Once the sum is infinite the correction is `inf - inf`; leave the
+        // compensation alone so the total stays infinite instead of NaN.
+        if !t.is_finite() {
+            self.sum = t;
+            return;
+        }
+        self.comp += if self.sum.abs() >= x.abs() {
+            (self.sum - t) + x
+        } else {
+            (x - t) + self.sum
+        };
+        self.sum = t;
+    }
+
+    #[inline(always)]
+    fn merge(&mut self, other: Self) {
+        self.add(other.sum);
+        self.comp += other.comp;
+    }
+
+    #[inline(always)]
+    fn value(self) -> f32 {
+        self.sum + self.comp
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn ilp_sum_matches_native_sum() {
+        let data = [1.0f32; 10];
+        let n = data.len() as f32;
+        assert!((ilp_sum(&data) - native_sum(&data)).abs() <= f32::EPSILON * n);
+    }
+
+    #[test]
+    fn ilp_sum_n_single_lane_is_plain_sum() {
+        let data = [0.5f32, 1.5, 2.5, 3.5, 4.5];
+        assert_eq!(ilp_sum_n::<1>(&data), native_sum(&data));
+    }
+
+    #[test]
+    fn ilp_sum_n_handles_input_shorter_than_lanes() {
+        assert_eq!(ilp_sum_n::<8>(&[1.0, 2.0, 3.0]), 6.0);
+        assert_eq!(ilp_sum_n::<8>(&[]), 0.0);
+    }
+
+    #[test]
+    fn ilp_sum_n_folds_remainder() {
+        let data: Vec<f32> = (1..=19).map(|i| i as f32).collect();
+        assert_eq!(ilp_sum_n::<2>(&data), 190.0);
+        assert_eq!(ilp_sum_n::<4>(&data), 190.0);
+        assert_eq!(ilp_sum_n::<8>(&data), 190.0);
+    }
+
+    #[test]
+    fn ilp_sum_f64_keeps_small_terms_f32_loses() {
+        let mut data64 = vec![1.0f64; 1001];
+        data64[0] = 1e16;
+        let data32: Vec<f32> = data64.iter().map(|&x| x as f32).collect();
+
+        assert_eq!(ilp_sum(&data32), 1e16f32);
+        assert!(ilp_sum(&data64) > 1e16);
+    }
+
+    #[test]
+    fn ilp_sum_kahan_recovers_cancelled_terms_across_lanes() {
+        let data = [1e8f32, 1.0, -1e8, 1.0];
+        assert_eq!(ilp_sum(&data), 1.0);
+        assert_eq!(ilp_sum_kahan(&data), 2.0);
+    }
+
+    #[test]
+    fn ilp_sum_kahan_recovers_cancelled_terms_within_lanes() {
+        let mut data = vec![1e8f32; 4];
+        data.extend([1.0; 400]);
+        data.extend([-1e8; 4]);
+        data.push(1.0);
+
+        let exact = 401.0;
+        let plain_err = (ilp_sum(&data) - exact).abs();
+        let kahan_err = (ilp_sum_kahan(&data) - exact).abs();
+        assert!(kahan_err < plain_err);
+        assert_eq!(ilp_sum_kahan(&data), exact);
+    }
+
+    #[test]
+    fn ilp_sum_kahan_stays_infinite() {
+        let inf = f32::INFINITY;
+        assert_eq!(ilp_sum_kahan(&[inf]), inf);
+        assert_eq!(ilp_sum_kahan(&[inf, 1.0, 2.0, 3.0, 4.0]), inf);
+        assert_eq!(ilp_sum_kahan(&[1.0, 2.0, 3.0, 4.0, -inf]), -inf);
+        assert_eq!(ilp_sum_kahan(&[f32::MAX, f32::MAX]), inf);
+        assert!(ilp_sum_kahan(&[inf, -inf]).is_nan());
+    }
+
+    #[test]
+    fn ilp_sum_pairwise_error_stays_logarithmic() {
+        let n = 1_000_000;
+        let data = vec![0.1f32; n];
+        let exact = 0.1f32 as f64 * n as f64;
+        let rel_err = |sum: f32| ((sum as f64 - exact) / exact).abs();
+
+        let pairwise = rel_err(ilp_sum_pairwise(&data));
+        let plain = rel_err(native_sum(&data));
+        // log2(n) roundings of at most half an ulp each, against the n of
+        // the sequential sum.
+        let log_bound = (n as f64).log2() * f32::EPSILON as f64;
+        assert!(pairwise <= log_bound, "pairwise error {pairwise}");
+        assert!(plain > log_bound, "plain error {plain}");
+        assert!(pairwise * 100.0 < plain);
+    }
+
+    #[test]
+    fn ilp_sum_pairwise_handles_partial_blocks() {
+        for len in [0usize, 1, 127, 128, 129, 3 * 128 + 5] {
+            let data: Vec<f32> = (0..len).map(|i| i as f32).collect();
+            let expected = (len * len.saturating_sub(1) / 2) as f32;
+            assert_eq!(ilp_sum_pairwise(&data), expected, "len {len}");
+        }
+    }
+
+    #[test]
+    fn ilp_dot_matches_naive_reference() {
+        let a: Vec<f32> = (0..11).map(|i| i as f32 * 0.5).collect();
+        let b: Vec<f32> = (0..11).map(|i| 3.0 - i as f32).collect();
+        let expected: f32 = a.iter().zip(&b).map(|(x, y)| x * y).sum();
+        assert!((ilp_dot(&a, &b) - expected).abs() <= f32::EPSILON * expected.abs());
+    }
+
+    #[test]
+    #[should_panic(expected = "equal length")]
+    fn ilp_dot_rejects_length_mismatch() {
+        ilp_dot(&[1.0, 2.0], &[1.0]);
+    }
+
+    #[test]
+    fn ilp_min_max_ignore_nan() {
+        let data = [1.0, f32::NAN, 3.0];
+        assert_eq!(ilp_min(&data), Some(1.0));
+        assert_eq!(ilp_max(&data), Some(3.0));
+    }
+
+    #[test]
+    fn ilp_min_max_cover_every_lane_and_tail() {
+        let data = [4.0, 9.0, -2.0, 5.0, 7.0, 0.5, 8.0, 6.0, -3.0];
+        assert_eq!(ilp_min(&data), Some(-3.0));
+        assert_eq!(ilp_max(&data), Some(9.0));
+        assert_eq!(ilp_min(&[]), None);
+        assert_eq!(ilp_max(&[]), None);
+    }
+
+    #[test]
+    fn ilp_min_max_matches_separate_passes() {
+        let data: Vec<f32> = (0..23).map(|i| ((i * 7) % 11) as f32 - 5.0).collect();
+        let expected = (ilp_min(&data).unwrap(), ilp_max(&data).unwrap());
+        assert_eq!(ilp_min_max(&data), Some(expected));
+        assert_eq!(ilp_min_max(&[f32::NAN, 2.0]), Some((2.0, 2.0)));
+        assert_eq!(ilp_min_max(&[]), None);
+    }
+
+    #[test]
+    fn ilp_sum_iter_matches_closed_form() {
+        assert_eq!(
+            ilp_sum_iter((0..1000).map(|i| i as f32)),
+            999.0 * 1000.0 / 2.0
+        );
+        assert_eq!(ilp_sum_iter((1..=6).map(|i| i as f32)), 21.0);
+        assert_eq!(ilp_sum_iter(core::iter::empty()), 0.0);
+    }
+
+    #[test]
+    fn ilp_sum_sq_covers_tail() {
+        assert_eq!(ilp_sum_sq(&[1.0, 2.0, 3.0, 4.0, 5.0, -6.0]), 91.0);
+        assert_eq!(ilp_sum_sq(&[]), 0.0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn ilp_l2_norm_of_3_4_is_5() {
+        assert_eq!(ilp_l2_norm(&[3.0, 4.0]), 5.0);
+    }
+
+    #[test]
+    fn ilp_sum_strided_sums_a_matrix_column() {
+        #[rustfmt::skip]
+        let matrix = [
+            1.0, 2.0, 3.0,
+            4.0, 5.0, 6.0,
+            7.0, 8.0, 9.0,
+            10.0, 11.0, 12.0,
+        ];
+        assert_eq!(ilp_sum_strided(&matrix, 3, 1), 2.0 + 5.0 + 8.0 + 11.0);
+        assert_eq!(ilp_sum_strided(&matrix, 1, 0), ilp_sum(&matrix));
+        assert_eq!(ilp_sum_strided(&matrix, 3, 12), 0.0);
+        assert_eq!(ilp_sum_strided(&matrix, 3, 40), 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "stride must be non-zero")]
+    fn ilp_sum_strided_rejects_zero_stride() {
+        ilp_sum_strided(&[1.0], 0, 0);
+    }
+
+    #[test]
+    fn ilp_nansum_skips_nan_and_counts_values() {
+        assert_eq!(ilp_nansum(&[1.0, f32::NAN, 3.0]), (4.0, 2));
+        let data = [f32::NAN, 2.0, 2.0, f32::NAN, 1.0, f32::NAN, 4.0, 1.0, 5.0];
+        assert_eq!(ilp_nansum(&data), (15.0, 6));
+        assert_eq!(ilp_nansum(&[f32::NAN; 5]), (0.0, 0));
+        assert_eq!(ilp_nansum(&[]), (0.0, 0));
+    }
+
+    #[test]
+    fn try_variants_report_errors() {
+        assert_eq!(
+            try_ilp_dot(&[1.0], &[1.0, 2.0]),
+            Err(PrepError::LengthMismatch { a: 1, b: 2 })
+        );
+        assert_eq!(try_ilp_dot(&[2.0], &[3.0]), Ok(6.0));
+        assert!(matches!(
+            try_ilp_sum_strided(&[1.0], 0, 0),
+            Err(PrepError::InvalidParam(_))
+        ));
+    }
+
+    #[test]
+    fn ilp_product_multiplies_all_lanes() {
+        assert_eq!(ilp_product(&[2.0, 3.0, 4.0]), 24.0);
+        assert_eq!(ilp_product(&[1.0, 2.0, 3.0, 4.0, 5.0, 0.5]), 60.0);
+        assert_eq!(ilp_product(&[]), 1.0);
+    }
+
+    #[test]
+    fn ilp_sum_handles_integers() {
+        let data: Vec<i64> = (1..=1001).collect();
+        assert_eq!(ilp_sum(&data), 1001 * 1002 / 2);
+        assert_eq!(ilp_sum(&[i32::MAX, 1]), i32::MIN);
+    }
+
+    #[test]
+    fn ilp_sum_checked_detects_overflow() {
+        assert_eq!(ilp_sum_checked(&[i32::MAX, 1]), None);
+        assert_eq!(ilp_sum_checked(&[i32::MAX, 0, 0, 0, 1]), None);
+        assert_eq!(ilp_sum_checked(&[1i32, 2, 3, 4, 5]), Some(15));
+        assert_eq!(ilp_sum_checked(&[i64::MIN, -1]), None);
+        assert_eq!(ilp_sum_checked(&[f32::MAX, f32::MAX]), None);
+        assert_eq!(ilp_sum_checked(&[1.5f64, 2.5]), Some(4.0));
+    }
+
+    #[test]
+    fn ilp_sum_exact_matches_ilp_sum_on_aligned_lengths() {
+        let data: Vec<f32> = (0..16).map(|i| i as f32 * 0.5).collect();
+        assert_eq!(ilp_sum_exact(&data), ilp_sum(&data));
+        assert_eq!(ilp_sum_exact(&[]), 0.0);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "not a multiple of 4")]
+    fn ilp_sum_exact_rejects_ragged_length_in_debug() {
+        ilp_sum_exact(&[1.0; 5]);
+    }
+
+    #[test]
+    fn ilp_reduce_folds_with_combine() {
+        assert_eq!(
+            ilp_reduce(&[1.0, 2.0, 3.0, 4.0, 5.0], 0.0, |a, b| a + b),
+            15.0
+        );
+        assert_eq!(ilp_reduce(&[3u32, 9, 2, 7, 4, 8], 0, u32::max), 9);
+        assert_eq!(ilp_reduce(&[], 1.0f32, |a, b| a * b), 1.0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn cosine_similarity_of_known_angles() {
+        let a: Vec<f32> = (0..9).map(|i| i as f32 - 3.5).collect();
+        let neg: Vec<f32> = a.iter().map(|x| -x).collect();
+        assert!((cosine_similarity(&a, &a).unwrap() - 1.0).abs() < 1e-6);
+        assert!((cosine_similarity(&a, &neg).unwrap() + 1.0).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), Some(0.0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn cosine_similarity_handles_extreme_magnitudes() {
+        let big = [3e20f32, -1e25, 2e30, 5e19, 1e38];
+        let neg: Vec<f32> = big.iter().map(|x| -x).collect();
+        assert_eq!(cosine_similarity(&big, &big), Some(1.0));
+        assert_eq!(cosine_similarity(&big, &neg), Some(-1.0));
+        let tiny = [1e-30f32, 0.0, 2e-30];
+        assert!((cosine_similarity(&tiny, &[2.0, 0.0, 4.0]).unwrap() - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, f32::INFINITY], &[1.0, 1.0])
+            .unwrap()
+            .is_nan());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn cosine_similarity_is_none_for_zero_norm() {
+        assert_eq!(cosine_similarity(&[0.0; 5], &[1.0; 5]), None);
+        assert_eq!(cosine_similarity(&[], &[]), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_cosine_similarity_reports_length_mismatch() {
+        assert_eq!(
+            try_cosine_similarity(&[1.0; 2], &[1.0; 3]),
+            Err(PrepError::LengthMismatch { a: 2, b: 3 })
+        );
+    }
+
+    #[test]
+    fn ilp_sum_blocked_matches_ilp_sum_up_to_rounding() {
+        let data: Vec<f32> = (0..1000).map(|i| (i % 17) as f32 * 0.5).collect();
+        let expected = ilp_sum(&data);
+        for block in [1, 3, 64, 1000, 4096] {
+            assert_eq!(ilp_sum_blocked(&data, block), expected, "block {block}");
+        }
+        assert_eq!(ilp_sum_blocked(&[], 8), 0.0);
+    }
+
+    #[test]
+    fn try_ilp_sum_blocked_rejects_zero_block() {
+        assert_eq!(
+            try_ilp_sum_blocked(&[1.0], 0),
+            Err(PrepError::InvalidParam("block must be non-zero"))
+        );
+    }
+
+    #[test]
+    fn ilp_sum_deterministic_matches_native_sum_bits() {
+        let data: Vec<f32> = (0..1001).map(|i| 1.0 / (i as f32 + 0.3) - 0.7).collect();
+        for len in [0, 1, 7, 1001] {
+            let slice = &data[..len];
+            assert_eq!(
+                ilp_sum_deterministic(slice).to_bits(),
+                native_sum(slice).to_bits(),
+                "len {len}"
+            );
+        }
+        assert_eq!(
+            ilp_sum_deterministic(&[-0.0, -0.0]).to_bits(),
+            (-0.0f32).to_bits()
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Scans: running totals and windows over a slice.
+
+use crate::error::{check_len, PrepError};
+use crate::reduce::ilp_sum;
+
+/// Inclusive prefix sum: `out[i] = data[0] + ... + data[i]`.
+///
+/// A plain running sum is one long dependency chain. Here the slice is cut
+/// into four equal blocks and solved in two passes: first the totals of the
+/// leading blocks are computed with the unrolled [`ilp_sum`] to get each
+/// block's starting offset, then the four blocks are scanned interleaved,
+/// each with its own running total, so four chains advance side by side.
+/// The few elements left over after the four blocks continue the last chain.
+///
+/// `out[i]` equals the sequential running sum up to float rounding, since
+/// the block offsets are summed in a different order.
+///
+/// # Panics
+///
+/// Panics if `out.len() != data.len()`; see [`try_ilp_prefix_sum`].
+pub fn ilp_prefix_sum(data: &[f32], out: &mut [f32]) {
+    try_ilp_prefix_sum(data, out).unwrap_or_else(|e| panic!("ilp_prefix_sum: {e}"))
+}
+
+/// [`ilp_prefix_sum`] returning [`PrepError::LengthMismatch`] instead of
+/// panicking. `out` is untouched on error.
+pub fn try_ilp_prefix_sum(data: &[f32], out: &mut [f32]) -> Result<(), PrepError> {
+    check_len(data.len(), out.len())?;
+    let block = data.len() / 4;
+    let (body, tail) = data.split_at(block * 4);
+    let (out_body, out_tail) = out.split_at_mut(block * 4);
+
+    let mut carry = 0.0;
+    if block > 0 {
+        let (d0, rest) = body.split_at(block);
+        let (d1, rest) = rest.split_at(block);
+        let (d2, d3) = rest.split_at(block);
+        let (o0, rest) = out_body.split_at_mut(block);
+        let (o1, rest) = rest.split_at_mut(block);
+        let (o2, o3) = rest.split_at_mut(block);
+
+        let mut acc0 = 0.0;
+        let mut acc1 = ilp_sum(d0);
+        let mut acc2 = acc1 + ilp_sum(d1);
+        let mut acc3 = acc2 + ilp_sum(d2);
+        for i in 0..block {
+            acc0 += d0[i];
+            acc1 += d1[i];
+            acc2 += d2[i];
+            acc3 += d3[i];
+            o0[i] = acc0;
+            o1[i] = acc1;
+            o2[i] = acc2;
+            o3[i] = acc3;
+        }
+        carry = acc3;
+    }
+    for (o, &x) in out_tail.iter_mut().zip(tail) {
+        carry += x;
+        *o = carry;
+    }
+    Ok(())
+}
+
+/// Trailing moving average: `out[i]` is the mean of the last `window`
+/// elements up to and including `data[i]`.
+///
+/// The first `window - 1` outputs are partial averages over the elements seen
+/// so far (`out[0] = data[0]`, `out[1] = (data[0] + data[1]) / 2`, ...)
+/// rather than NaN. The window sum is updated incrementally, adding the
+/// element that enters and subtracting the one that leaves, so the cost is
+/// O(n) regardless of `window`; the incremental sum can pick up rounding
+/// drift over very long inputs.
+///
+/// # Panics
+///
+/// Panics if `window` is zero or `out.len() != data.len()`; see
+/// [`try_moving_average`].
+pub fn moving_average(data: &[f32], window: usize, out: &mut [f32]) {
+    try_moving_average(data, window, out).unwrap_or_else(|e| panic!("moving_average: {e}"))
+}
+
+/// [`moving_average`] returning a [`PrepError`] instead of panicking. `out`
+/// is untouched on error.
+pub fn try_moving_average(data: &[f32], window: usize, out: &mut [f32]) -> Result<(), PrepError> {
+    if window == 0 {
+        return Err(PrepError::InvalidParam("window must be non-zero"));
+    }
+    check_len(data.len(), out.len())?;
+    let warmup = window.min(data.len());
+    let mut sum = 0.0;
+    for (i, (o, &x)) in out[..warmup].iter_mut().zip(data).enumerate() {
+        sum += x;
+        *o = sum / (i + 1) as f32;
+    }
+    let inv_window = 1.0 / window as f32;
+    let entering = &data[warmup..];
+    for ((o, &x), &old) in out[warmup..].iter_mut().zip(entering).zip(data) {
+        sum += x - old;
+        *o = sum * inv_window;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn reference_scan(data: &[f32]) -> Vec<f32> {
+        data.iter()
+            .scan(0.0, |acc, &x| {
+                *acc += x;
+                Some(*acc)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ilp_prefix_sum_matches_reference_scan() {
+        let data = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let mut out = [0.0; 5];
+        ilp_prefix_sum(&data, &mut out);
+        assert_eq!(out, [1.0, 3.0, 6.0, 10.0, 15.0]);
+
+        for len in [0, 1, 3, 4, 17, 64, 103] {
+            let data: Vec<f32> = (0..len).map(|i| ((i * 13) % 7) as f32).collect();
+            let mut out = vec![0.0; len];
+            ilp_prefix_sum(&data, &mut out);
+            assert_eq!(out, reference_scan(&data), "len {len}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "equal length")]
+    fn ilp_prefix_sum_rejects_length_mismatch() {
+        ilp_prefix_sum(&[1.0, 2.0], &mut [0.0]);
+    }
+
+    #[test]
+    fn moving_average_uses_partial_warmup_windows() {
+        let data = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let mut out = [0.0; 5];
+        moving_average(&data, 3, &mut out);
+        assert_eq!(out, [1.0, 1.5, 2.0, 3.0, 4.0]);
+
+        moving_average(&data, 1, &mut out);
+        assert_eq!(out, data);
+
+        moving_average(&data, 10, &mut out);
+        assert_eq!(out, [1.0, 1.5, 2.0, 2.5, 3.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "window must be non-zero")]
+    fn moving_average_rejects_zero_window() {
+        moving_average(&[1.0], 0, &mut [0.0]);
+    }
+
+    #[test]
+    fn try_variants_report_errors() {
+        let mut out = [0.0; 2];
+        assert_eq!(
+            try_ilp_prefix_sum(&[1.0], &mut out),
+            Err(PrepError::LengthMismatch { a: 1, b: 2 })
+        );
+        assert_eq!(
+            try_moving_average(&[1.0, 2.0], 0, &mut out),
+            Err(PrepError::InvalidParam("window must be non-zero"))
+        );
+        assert_eq!(try_moving_average(&[1.0, 2.0], 2, &mut out), Ok(()));
+        assert_eq!(out, [1.0, 1.5]);
+    }
+}
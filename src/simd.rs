@@ -0,0 +1,158 @@
+//! Hand-vectorised kernels selected at runtime.
+
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+use crate::reduce::ilp_sum_scalar;
+
+/// Sums `data` with AVX2 when the running CPU supports it.
+///
+/// The feature check is done once per call, before the loop, so the hot path
+/// never branches on it.
+#[inline]
+pub(crate) fn sum_f32(data: &[f32]) -> f32 {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if has_avx2() {
+            // SAFETY: AVX2 support was just checked.
+            return unsafe { sum_f32_avx2(data) };
+        }
+    }
+    ilp_sum_scalar(data)
+}
+
+/// Runtime detection (a cached load) with `std`; without it only a crate
+/// compiled with `target-feature=+avx2` takes the AVX2 path.
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+fn has_avx2() -> bool {
+    #[cfg(feature = "std")]
+    {
+        std::is_x86_feature_detected!("avx2")
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        cfg!(target_feature = "avx2")
+    }
+}
+
+/// Four independent 8-wide accumulators, i.e. 32 lanes per iteration.
+///
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn sum_f32_avx2(data: &[f32]) -> f32 {
+    let mut acc0 = _mm256_setzero_ps();
+    let mut acc1 = _mm256_setzero_ps();
+    let mut acc2 = _mm256_setzero_ps();
+    let mut acc3 = _mm256_setzero_ps();
+    let chunks = data.chunks_exact(32);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        let p = chunk.as_ptr();
+        acc0 = _mm256_add_ps(acc0, _mm256_loadu_ps(p));
+        acc1 = _mm256_add_ps(acc1, _mm256_loadu_ps(p.add(8)));
+        acc2 = _mm256_add_ps(acc2, _mm256_loadu_ps(p.add(16)));
+        acc3 = _mm256_add_ps(acc3, _mm256_loadu_ps(p.add(24)));
+    }
+    let acc = _mm256_add_ps(_mm256_add_ps(acc0, acc1), _mm256_add_ps(acc2, acc3));
+    let mut lanes = [0.0f32; 8];
+    _mm256_storeu_ps(lanes.as_mut_ptr(), acc);
+    ilp_sum_scalar(&lanes) + ilp_sum_scalar(remainder)
+}
+
+/// Eight `f32`s on a 32-byte boundary: one aligned AVX register load.
+#[repr(C, align(32))]
+#[derive(Clone, Copy)]
+struct Block([f32; 8]);
+
+/// Sums `data` as an unaligned scalar head, a 32-byte-aligned body and a
+/// scalar tail, so the body can use aligned loads.
+#[inline]
+pub(crate) fn sum_f32_aligned(data: &[f32]) -> f32 {
+    // SAFETY: `Block` is a plain `[f32; 8]` with no padding, so every
+    // aligned run of eight `f32`s is a valid `Block`.
+    let (head, body, tail) = unsafe { data.align_to::<Block>() };
+    let body_sum = {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if has_avx2() {
+                // SAFETY: AVX2 support was just checked.
+                unsafe { sum_blocks_avx2(body) }
+            } else {
+                ilp_sum_scalar(&data[head.len()..data.len() - tail.len()])
+            }
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            ilp_sum_scalar(&data[head.len()..data.len() - tail.len()])
+        }
+    };
+    ilp_sum_scalar(head) + body_sum + ilp_sum_scalar(tail)
+}
+
+/// [`sum_f32_avx2`] over pre-aligned blocks, using aligned loads.
+///
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn sum_blocks_avx2(body: &[Block]) -> f32 {
+    let mut acc0 = _mm256_setzero_ps();
+    let mut acc1 = _mm256_setzero_ps();
+    let mut acc2 = _mm256_setzero_ps();
+    let mut acc3 = _mm256_setzero_ps();
+    let chunks = body.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        acc0 = _mm256_add_ps(acc0, _mm256_load_ps(chunk[0].0.as_ptr()));
+        acc1 = _mm256_add_ps(acc1, _mm256_load_ps(chunk[1].0.as_ptr()));
+        acc2 = _mm256_add_ps(acc2, _mm256_load_ps(chunk[2].0.as_ptr()));
+        acc3 = _mm256_add_ps(acc3, _mm256_load_ps(chunk[3].0.as_ptr()));
+    }
+    for block in remainder {
+        acc0 = _mm256_add_ps(acc0, _mm256_load_ps(block.0.as_ptr()));
+    }
+    let acc = _mm256_add_ps(_mm256_add_ps(acc0, acc1), _mm256_add_ps(acc2, acc3));
+    let mut lanes = Block([0.0; 8]);
+    _mm256_store_ps(lanes.0.as_mut_ptr(), acc);
+    ilp_sum_scalar(&lanes.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn avx2_sum_matches_scalar() {
+        if !has_avx2() {
+            return;
+        }
+        for len in [0, 7, 32, 33, 1000, 4099] {
+            let data: Vec<f32> = (0..len).map(|i| ((i * 37) % 101) as f32 * 0.01).collect();
+            let scalar = ilp_sum_scalar(&data);
+            // SAFETY: AVX2 support was checked above.
+            let simd = unsafe { sum_f32_avx2(&data) };
+            let tolerance = f32::EPSILON * len as f32 * scalar.abs().max(1.0);
+            assert!((simd - scalar).abs() <= tolerance, "len {len}");
+        }
+    }
+
+    #[test]
+    fn aligned_sum_ignores_starting_alignment() {
+        let data: Vec<f32> = (0..1027).map(|i| (i % 13) as f32).collect();
+        let full = ilp_sum_scalar(&data);
+        assert_eq!(sum_f32_aligned(&data), full);
+        for offset in 1..=3 {
+            let sub = &data[offset..];
+            assert_eq!(sum_f32_aligned(sub), ilp_sum_scalar(sub), "offset {offset}");
+        }
+        assert_eq!(sum_f32_aligned(&data[..5]), ilp_sum_scalar(&data[..5]));
+        assert_eq!(sum_f32_aligned(&[]), 0.0);
+    }
+}
@@ -0,0 +1,728 @@
+//! Descriptive statistics for feature preparation.
+
+use crate::error::PrepError;
+#[cfg(feature = "std")]
+use crate::reduce::ilp_max;
+use crate::reduce::InputLen;
+
+/// Mean and population variance of `data` in a single pass.
+///
+/// Each of the four lanes runs Welford's online update, which avoids the
+/// catastrophic cancellation of the naive `E[x²] - E[x]²` approach. The lanes
+/// are merged with the parallel-variance formula of Chan et al. Returns
+/// `None` for empty input.
+///
+/// The variance is the *population* variance (divided by `n`); multiply by
+/// `n / (n - 1)` for the unbiased sample estimate.
+pub fn ilp_mean_variance(data: &[f32]) -> Option<(f32, f32)> {
+    if InputLen::of(data) == InputLen::Empty {
+        return None;
+    }
+    let mut lanes = [Welford::EMPTY; 4];
+    let chunks = data.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        lanes[0].push(chunk[0]);
+        lanes[1].push(chunk[1]);
+        lanes[2].push(chunk[2]);
+        lanes[3].push(chunk[3]);
+    }
+    let mut total = lanes[0].merge(lanes[1]).merge(lanes[2].merge(lanes[3]));
+    for &x in remainder {
+        total.push(x);
+    }
+    Some((total.mean, total.m2 / total.count))
+}
+
+/// Geometric mean of `data`, `exp(mean(ln x))`.
+///
+/// Computed in log space with four lane accumulators, so it neither
+/// overflows nor underflows the way `ilp_product(data).powf(1 / n)` would.
+/// Returns `None` for empty input or if any element is `<= 0` or NaN, where
+/// the logarithm is undefined.
+#[cfg(feature = "std")]
+pub fn geometric_mean(data: &[f32]) -> Option<f32> {
+    if InputLen::of(data) == InputLen::Empty {
+        return None;
+    }
+    let mut logs = [0.0f32; 4];
+    let mut valid = true;
+    let chunks = data.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        for (acc, &x) in logs.iter_mut().zip(chunk) {
+            valid &= x > 0.0;
+            *acc += x.ln();
+        }
+    }
+    let mut log_sum = (logs[0] + logs[1]) + (logs[2] + logs[3]);
+    for &x in remainder {
+        valid &= x > 0.0;
+        log_sum += x.ln();
+    }
+    valid.then(|| (log_sum / data.len() as f32).exp())
+}
+
+/// `ln(sum(exp(x)))` over `data`, computed without overflow.
+///
+/// The maximum `m` is found with [`ilp_max`] first, then `exp(x - m)` is
+/// summed in four lanes and the result is `m + ln(sum)`. Every exponent is at
+/// most zero, so large logits such as `1000.0` stay finite. Returns
+/// `f32::NEG_INFINITY` for empty input (the log of an empty sum), and `m`
+/// itself when the maximum is infinite. Any NaN makes the result NaN.
+#[cfg(feature = "std")]
+pub fn log_sum_exp(data: &[f32]) -> f32 {
+    let Some(max) = ilp_max(data) else {
+        return f32::NEG_INFINITY;
+    };
+    // `ilp_max` skips NaN, so check for one before the infinite shortcut.
+    if max.is_infinite() {
+        return if data.iter().any(|x| x.is_nan()) {
+            f32::NAN
+        } else {
+            max
+        };
+    }
+    let mut sums = [0.0f32; 4];
+    let chunks = data.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        for (acc, &x) in sums.iter_mut().zip(chunk) {
+            *acc += (x - max).exp();
+        }
+    }
+    let mut sum = (sums[0] + sums[1]) + (sums[2] + sums[3]);
+    for &x in remainder {
+        sum += (x - max).exp();
+    }
+    max + sum.ln()
+}
+
+/// Number of buckets used by [`approx_quantile`]: one for every combination
+/// of the sign, the exponent and the top five mantissa bits of an `f32`.
+pub const QUANTILE_BINS: usize = 1 << 14;
+
+/// Low bits of a [`quantile_key`] that fall inside one bucket.
+const QUANTILE_SHIFT: u32 = 32 - QUANTILE_BINS.trailing_zeros();
+
+/// Approximate `q`-quantile of `data`, e.g. `q = 0.99` for the 99th
+/// percentile, without sorting.
+///
+/// A single four-lane pass counts the elements per bucket, four bucket
+/// indices at a time, while tracking the minimum and maximum. The buckets are
+/// the [`QUANTILE_BINS`] slices of the `f32` bit pattern that share a sign,
+/// an exponent and the top five mantissa bits, so no range is needed up
+/// front and outliers do not coarsen the buckets of the bulk. The bucket
+/// holding the element of rank `q * (n - 1)` is found from the cumulative
+/// counts, and the estimate is interpolated linearly inside it and clamped
+/// to `[min, max]`. Each bucket spans at most `2^-5` of the magnitude of its
+/// values, so for normal values the relative error is at most `2^-5` (about
+/// 3%); in the subnormal range the absolute error is at most `2^-131`. The
+/// counts take 128 KiB of stack.
+///
+/// NaNs are ignored. Infinities keep their rank, so the estimate is `-inf`
+/// or `inf` when that rank falls on one. Returns `None` if `data` is empty
+/// or all NaN.
+///
+/// # Panics
+///
+/// Panics if `q` is outside `[0, 1]`; see [`try_approx_quantile`].
+pub fn approx_quantile(data: &[f32], q: f32) -> Option<f32> {
+    match try_approx_quantile(data, q) {
+        Ok(estimate) => Some(estimate),
+        Err(PrepError::EmptyInput) => None,
+        Err(e) => panic!("approx_quantile: {e}"),
+    }
+}
+
+/// [`approx_quantile`] returning [`PrepError::EmptyInput`] for empty or
+/// all-NaN input and [`PrepError::InvalidParam`] for `q` outside `[0, 1]`.
+pub fn try_approx_quantile(data: &[f32], q: f32) -> Result<f32, PrepError> {
+    if !(0.0..=1.0).contains(&q) {
+        return Err(PrepError::InvalidParam("q must be in [0, 1]"));
+    }
+    let mut counts = [0u64; QUANTILE_BINS];
+    let (min, max) = count_quantile_buckets(data, &mut counts).ok_or(PrepError::EmptyInput)?;
+    if min == max {
+        return Ok(min);
+    }
+
+    let n: u64 = counts.iter().sum();
+    let rank = f64::from(q) * (n - 1) as f64;
+    let mut below = 0;
+    for (bucket, &count) in counts.iter().enumerate() {
+        if count > 0 && rank < (below + count) as f64 {
+            let frac = (rank - below as f64 + 0.5) / count as f64;
+            return Ok(bucket_value(bucket, frac).clamp(min, max));
+        }
+        below += count;
+    }
+    Ok(max)
+}
+
+/// Adds every non-NaN element of `data` to its [`quantile_key`] bucket in
+/// `counts`, and returns the smallest and largest of them, or `None` if there
+/// are none.
+#[inline(always)]
+fn count_quantile_buckets(data: &[f32], counts: &mut [u64; QUANTILE_BINS]) -> Option<(f32, f32)> {
+    // NaN is the identity of `f32::min`/`f32::max`.
+    let mut mins = [f32::NAN; 4];
+    let mut maxs = [f32::NAN; 4];
+    let chunks = data.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        let b0 = quantile_bucket(chunk[0]);
+        let b1 = quantile_bucket(chunk[1]);
+        let b2 = quantile_bucket(chunk[2]);
+        let b3 = quantile_bucket(chunk[3]);
+        counts[b0] += !chunk[0].is_nan() as u64;
+        counts[b1] += !chunk[1].is_nan() as u64;
+        counts[b2] += !chunk[2].is_nan() as u64;
+        counts[b3] += !chunk[3].is_nan() as u64;
+        for ((lo, hi), &x) in mins.iter_mut().zip(&mut maxs).zip(chunk) {
+            *lo = lo.min(x);
+            *hi = hi.max(x);
+        }
+    }
+    let mut min = mins[0].min(mins[1]).min(mins[2].min(mins[3]));
+    let mut max = maxs[0].max(maxs[1]).max(maxs[2].max(maxs[3]));
+    for &x in remainder {
+        counts[quantile_bucket(x)] += !x.is_nan() as u64;
+        min = min.min(x);
+        max = max.max(x);
+    }
+    (!min.is_nan()).then_some((min, max))
+}
+
+/// The bits of `x` rearranged so that unsigned comparison of keys orders
+/// them like the values: negatives have all bits flipped, positives only the
+/// sign bit.
+#[inline(always)]
+fn quantile_key(x: f32) -> u32 {
+    let bits = x.to_bits();
+    bits ^ (((bits as i32) >> 31) as u32 | 1 << 31)
+}
+
+/// Inverse of [`quantile_key`].
+#[inline(always)]
+fn quantile_key_value(key: u32) -> f32 {
+    f32::from_bits(if key >> 31 == 1 { key ^ 1 << 31 } else { !key })
+}
+
+#[inline(always)]
+fn quantile_bucket(x: f32) -> usize {
+    (quantile_key(x) >> QUANTILE_SHIFT) as usize
+}
+
+/// Value at fraction `frac` of the way through `bucket`.
+///
+/// A bucket never straddles a power of two, so the value is linear in the
+/// key inside it. The buckets of `-inf` and `inf` also hold NaN bit
+/// patterns, which are never counted; their infinite end is returned.
+fn bucket_value(bucket: usize, frac: f64) -> f32 {
+    let lo = (bucket as u32) << QUANTILE_SHIFT;
+    let hi = lo | ((1 << QUANTILE_SHIFT) - 1);
+    let (lo_value, hi_value) = (quantile_key_value(lo), quantile_key_value(hi));
+    if lo_value.is_nan() {
+        return hi_value;
+    }
+    if hi_value.is_nan() {
+        return lo_value;
+    }
+    let offset = (frac * f64::from(1u32 << QUANTILE_SHIFT)) as u32;
+    quantile_key_value((lo + offset).min(hi))
+}
+
+/// Equal-width histogram of `data` over `[min, max]` with `bins` buckets.
+///
+/// Bucket `i` covers `[min + i * w, min + (i + 1) * w)` with
+/// `w = (max - min) / bins`; the last bucket also includes `max`. Values are
+/// clamped rather than dropped: anything below `min` is counted in bucket 0
+/// and anything above `max` in the last bucket. NaNs are not counted. Bucket
+/// indices are computed four elements at a time, in `f64` so that a range
+/// wider than `f32::MAX` does not overflow.
+///
+/// # Panics
+///
+/// Panics if `bins` is zero, `min >= max` (including NaN bounds) or either
+/// bound is infinite; see [`try_histogram`].
+#[cfg(feature = "std")]
+pub fn histogram(data: &[f32], min: f32, max: f32, bins: usize) -> Vec<u64> {
+    try_histogram(data, min, max, bins).unwrap_or_else(|e| panic!("histogram: {e}"))
+}
+
+/// [`histogram`] returning [`PrepError::InvalidParam`] instead of panicking.
+#[cfg(feature = "std")]
+pub fn try_histogram(data: &[f32], min: f32, max: f32, bins: usize) -> Result<Vec<u64>, PrepError> {
+    if bins == 0 {
+        return Err(PrepError::InvalidParam("bins must be non-zero"));
+    }
+    if min >= max || min.is_nan() || max.is_nan() {
+        return Err(PrepError::InvalidParam("min must be less than max"));
+    }
+    if min.is_infinite() || max.is_infinite() {
+        return Err(PrepError::InvalidParam("min and max must be finite"));
+    }
+    let mut counts = vec![0u64; bins];
+    let scale = bins as f64 / (f64::from(max) - f64::from(min));
+    count_bins(data, min, scale, &mut counts);
+    Ok(counts)
+}
+
+/// Adds every non-NaN element of `data` to its bucket in `counts`.
+#[cfg(feature = "std")]
+#[inline(always)]
+fn count_bins(data: &[f32], min: f32, scale: f64, counts: &mut [u64]) {
+    let bins = counts.len();
+    let chunks = data.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        let b0 = bin_index(chunk[0], min, scale, bins);
+        let b1 = bin_index(chunk[1], min, scale, bins);
+        let b2 = bin_index(chunk[2], min, scale, bins);
+        let b3 = bin_index(chunk[3], min, scale, bins);
+        counts[b0] += !chunk[0].is_nan() as u64;
+        counts[b1] += !chunk[1].is_nan() as u64;
+        counts[b2] += !chunk[2].is_nan() as u64;
+        counts[b3] += !chunk[3].is_nan() as u64;
+    }
+    for &x in remainder {
+        counts[bin_index(x, min, scale, bins)] += !x.is_nan() as u64;
+    }
+}
+
+/// Bucket of `x` among `bins` equal-width buckets starting at `min`, each
+/// `1 / scale` wide. Values outside the range land in the first or last
+/// bucket, and NaN in the first. `x - min` is taken in `f64`, where the
+/// difference of two finite `f32` values cannot overflow.
+#[cfg(feature = "std")]
+#[inline(always)]
+fn bin_index(x: f32, min: f32, scale: f64, bins: usize) -> usize {
+    // Float-to-int `as` saturates: negatives and NaN become 0.
+    (((f64::from(x) - f64::from(min)) * scale) as usize).min(bins - 1)
+}
+
+/// Dataset statistics gathered by [`summarize`].
+///
+/// With the `serde` feature it can be serialized, e.g. to reuse the scaling
+/// parameters of a training set at inference time with
+/// `apply_standardization` (available with `std`). Human-readable formats
+/// such as JSON have no NaN or infinity, so there the non-finite fields a
+/// NaN or infinite element produces are written as the strings `"NaN"`,
+/// `"inf"` and `"-inf"`, and read back from them; binary formats store the
+/// `f32` as is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Summary {
+    pub count: usize,
+    #[cfg_attr(feature = "serde", serde(with = "serde_f32"))]
+    pub sum: f32,
+    #[cfg_attr(feature = "serde", serde(with = "serde_f32"))]
+    pub min: f32,
+    #[cfg_attr(feature = "serde", serde(with = "serde_f32"))]
+    pub max: f32,
+    #[cfg_attr(feature = "serde", serde(with = "serde_f32"))]
+    pub mean: f32,
+    /// Population variance (divided by `count`).
+    #[cfg_attr(feature = "serde", serde(with = "serde_f32"))]
+    pub variance: f32,
+}
+
+/// `f32` fields of [`Summary`] that survive formats without NaN and infinity.
+#[cfg(feature = "serde")]
+mod serde_f32 {
+    use core::fmt;
+
+    use serde::{de, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(x: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+        if x.is_finite() || !serializer.is_human_readable() {
+            serializer.serialize_f32(*x)
+        } else if x.is_nan() {
+            serializer.serialize_str("NaN")
+        } else if *x > 0.0 {
+            serializer.serialize_str("inf")
+        } else {
+            serializer.serialize_str("-inf")
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(F32Visitor)
+        } else {
+            deserializer.deserialize_f32(F32Visitor)
+        }
+    }
+
+    struct F32Visitor;
+
+    impl de::Visitor<'_> for F32Visitor {
+        type Value = f32;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(r#"a number, "NaN", "inf" or "-inf""#)
+        }
+
+        fn visit_f32<E>(self, v: f32) -> Result<f32, E> {
+            Ok(v)
+        }
+
+        fn visit_f64<E>(self, v: f64) -> Result<f32, E> {
+            Ok(v as f32)
+        }
+
+        fn visit_i64<E>(self, v: i64) -> Result<f32, E> {
+            Ok(v as f32)
+        }
+
+        fn visit_u64<E>(self, v: u64) -> Result<f32, E> {
+            Ok(v as f32)
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<f32, E> {
+            match v {
+                "NaN" => Ok(f32::NAN),
+                "inf" => Ok(f32::INFINITY),
+                "-inf" => Ok(f32::NEG_INFINITY),
+                _ => Err(E::invalid_value(de::Unexpected::Str(v), &self)),
+            }
+        }
+    }
+}
+
+/// Count, sum, min, max, mean and population variance of `data`, all from a
+/// single read of the data.
+///
+/// Each of the four lanes keeps its own sum, extremes and Welford moments,
+/// merged at the end the same way as in
+/// [`ilp_min_max`](crate::ilp_min_max) and [`ilp_mean_variance`], so those
+/// fields match the dedicated functions and `sum` matches
+/// [`ilp_sum`](crate::ilp_sum) up to rounding. As there, `min`/`max` ignore
+/// NaN while a NaN element makes `sum`, `mean` and `variance` NaN. Returns
+/// `None` for empty input.
+pub fn summarize(data: &[f32]) -> Option<Summary> {
+    if InputLen::of(data) == InputLen::Empty {
+        return None;
+    }
+    let mut moments = [Welford::EMPTY; 4];
+    let mut sums = [0.0f32; 4];
+    let mut mins = [f32::NAN; 4];
+    let mut maxs = [f32::NAN; 4];
+    let chunks = data.chunks_exact(4);
+    let remainder = chunks.remainder();
+    for chunk in chunks {
+        for lane in 0..4 {
+            let x = chunk[lane];
+            moments[lane].push(x);
+            sums[lane] += x;
+            mins[lane] = mins[lane].min(x);
+            maxs[lane] = maxs[lane].max(x);
+        }
+    }
+    let mut total = moments[0]
+        .merge(moments[1])
+        .merge(moments[2].merge(moments[3]));
+    let mut sum = sums[0] + sums[1] + sums[2] + sums[3];
+    let mut min = mins[0].min(mins[1]).min(mins[2].min(mins[3]));
+    let mut max = maxs[0].max(maxs[1]).max(maxs[2].max(maxs[3]));
+    for &x in remainder {
+        total.push(x);
+        sum += x;
+        min = min.min(x);
+        max = max.max(x);
+    }
+    Some(Summary {
+        count: data.len(),
+        sum,
+        min,
+        max,
+        mean: total.mean,
+        variance: total.m2 / total.count,
+    })
+}
+
+/// Running count, mean and sum of squared deviations of one lane.
+#[derive(Clone, Copy)]
+struct Welford {
+    count: f32,
+    mean: f32,
+    m2: f32,
+}
+
+impl Welford {
+    const EMPTY: Self = Self {
+        count: 0.0,
+        mean: 0.0,
+        m2: 0.0,
+    };
+
+    #[inline(always)]
+    fn push(&mut self, x: f32) {
+        self.count += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.count;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    #[inline(always)]
+    fn merge(self, other: Self) -> Self {
+        let count = self.count + other.count;
+        if count == 0.0 {
+            return Self::EMPTY;
+        }
+        let delta = other.mean - self.mean;
+        let weight = other.count / count;
+        Self {
+            count,
+            mean: self.mean + delta * weight,
+            m2: self.m2 + other.m2 + delta * delta * self.count * weight,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reduce::ilp_min_max;
+    use std::vec::Vec;
+
+    fn two_pass(data: &[f32]) -> (f32, f32) {
+        let n = data.len() as f32;
+        let mean = data.iter().sum::<f32>() / n;
+        let var = data.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>() / n;
+        (mean, var)
+    }
+
+    #[test]
+    fn ilp_mean_variance_matches_two_pass() {
+        let data = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        assert_eq!(ilp_mean_variance(&data), Some((5.0, 4.0)));
+        assert_eq!(ilp_mean_variance(&data), Some(two_pass(&data)));
+    }
+
+    #[test]
+    fn ilp_mean_variance_handles_tail_and_empty() {
+        let data = [1.5, -2.0, 3.25, 0.0, 8.0, -1.0, 2.5];
+        let (mean, var) = ilp_mean_variance(&data).unwrap();
+        let (ref_mean, ref_var) = two_pass(&data);
+        assert!((mean - ref_mean).abs() < 1e-6);
+        assert!((var - ref_var).abs() < 1e-5);
+        assert_eq!(ilp_mean_variance(&[]), None);
+        assert_eq!(ilp_mean_variance(&[3.0]), Some((3.0, 0.0)));
+    }
+
+    /// Whether `estimate` is within one quantile bucket of `exact`: `2^-5`
+    /// relative for normal values, `2^-131` absolute for subnormal ones.
+    fn within_bucket(estimate: f32, exact: f32) -> bool {
+        (estimate - exact).abs() <= (exact.abs() / 32.0).max(f32::from_bits(1 << 18))
+    }
+
+    #[test]
+    fn approx_quantile_finds_median_and_tails() {
+        let data: Vec<f32> = (0..=100).map(|i| i as f32).collect();
+        let median = approx_quantile(&data, 0.5).unwrap();
+        assert!(within_bucket(median, 50.0), "median {median}");
+        let p99 = approx_quantile(&data, 0.99).unwrap();
+        assert!(within_bucket(p99, 99.0), "p99 {p99}");
+        let min = approx_quantile(&data, 0.0).unwrap();
+        assert!(within_bucket(min, 0.0), "min {min}");
+        assert_eq!(approx_quantile(&data, 1.0), Some(100.0));
+    }
+
+    #[test]
+    fn approx_quantile_is_not_coarsened_by_outliers() {
+        let mut data: Vec<f32> = (0..=100).map(|i| i as f32 * 0.01).collect();
+        data.extend([-1e30, 1e30]);
+        let median = approx_quantile(&data, 0.5).unwrap();
+        assert!(within_bucket(median, 0.5), "median {median}");
+    }
+
+    #[test]
+    fn approx_quantile_handles_degenerate_input() {
+        assert_eq!(approx_quantile(&[], 0.5), None);
+        assert_eq!(approx_quantile(&[f32::NAN, f32::NAN], 0.5), None);
+        assert_eq!(approx_quantile(&[7.0, 7.0, 7.0], 0.9), Some(7.0));
+        let median = approx_quantile(&[1.0, f32::NAN, 2.0, 3.0], 0.5).unwrap();
+        assert!(within_bucket(median, 2.0), "median {median}");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn histogram_bins_uniform_data_evenly() {
+        let data: Vec<f32> = (0..100).map(|i| i as f32).collect();
+        assert_eq!(histogram(&data, 0.0, 100.0, 10), vec![10; 10]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn histogram_clamps_out_of_range_and_skips_nan() {
+        let data = [-5.0, 0.0, 0.5, 1.0, 1.5, 2.0, 9.0, f32::NAN];
+        assert_eq!(histogram(&data, 0.0, 2.0, 2), vec![3, 4]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn histogram_handles_range_wider_than_f32_max() {
+        let data = [-3e38, 0.0, 3e38];
+        assert_eq!(histogram(&data, -3e38, 3e38, 4), vec![1, 0, 1, 1]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    #[should_panic(expected = "bins must be non-zero")]
+    fn histogram_rejects_zero_bins() {
+        histogram(&[1.0], 0.0, 1.0, 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    #[should_panic(expected = "min must be less than max")]
+    fn histogram_rejects_empty_range() {
+        histogram(&[1.0], 1.0, 1.0, 4);
+    }
+
+    #[test]
+    fn approx_quantile_keeps_infinities() {
+        let data = [0.0, 1.0, f32::INFINITY];
+        let median = approx_quantile(&data, 0.5).unwrap();
+        assert!(within_bucket(median, 1.0), "{median}");
+        assert_eq!(approx_quantile(&data, 1.0), Some(f32::INFINITY));
+
+        let data = [-3e38, 0.0, 1.0, 2.0, 3e38];
+        let median = approx_quantile(&data, 0.5).unwrap();
+        assert!(within_bucket(median, 1.0), "{median}");
+
+        let data = [f32::NEG_INFINITY, f32::INFINITY, f32::INFINITY];
+        assert_eq!(approx_quantile(&data, 0.0), Some(f32::NEG_INFINITY));
+        assert_eq!(approx_quantile(&data, 0.5), Some(f32::INFINITY));
+    }
+
+    #[test]
+    fn approx_quantile_handles_tiny_ranges() {
+        let data: Vec<f32> = (0..=100).map(|i| i as f32 * 1e-38).collect();
+        let median = approx_quantile(&data, 0.5).unwrap();
+        assert!(within_bucket(median, 50e-38), "{median}");
+
+        let subnormal: Vec<f32> = (0..=100).map(|i| i as f32 * 1e-40).collect();
+        let median = approx_quantile(&subnormal, 0.5).unwrap();
+        assert!(within_bucket(median, 50e-40), "{median}");
+    }
+
+    #[test]
+    fn approx_quantile_orders_negative_values() {
+        let data: Vec<f32> = (-100..=100).map(|i| i as f32 * 0.5).collect();
+        for (q, exact) in [(0.1, -40.0), (0.25, -25.0), (0.5, 0.0), (0.9, 40.0)] {
+            let estimate = approx_quantile(&data, q).unwrap();
+            assert!(within_bucket(estimate, exact), "q {q}: {estimate}");
+        }
+    }
+
+    #[test]
+    fn try_approx_quantile_reports_errors() {
+        assert_eq!(try_approx_quantile(&[], 0.5), Err(PrepError::EmptyInput));
+        assert_eq!(
+            try_approx_quantile(&[1.0], 1.5),
+            Err(PrepError::InvalidParam("q must be in [0, 1]"))
+        );
+        assert_eq!(try_approx_quantile(&[1.0], 0.5), Ok(1.0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_histogram_reports_errors() {
+        assert!(matches!(
+            try_histogram(&[1.0], 0.0, f32::NAN, 4),
+            Err(PrepError::InvalidParam(_))
+        ));
+        assert_eq!(
+            try_histogram(&[1.0], f32::NEG_INFINITY, 2.0, 2),
+            Err(PrepError::InvalidParam("min and max must be finite"))
+        );
+        assert_eq!(try_histogram(&[1.0], 0.0, 2.0, 2), Ok(vec![0, 1]));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn geometric_mean_works_in_log_space() {
+        assert!((geometric_mean(&[1.0, 4.0]).unwrap() - 2.0).abs() < 1e-6);
+        let tiny = [1e-30f32; 9];
+        assert!((geometric_mean(&tiny).unwrap() / 1e-30 - 1.0).abs() < 1e-4);
+        assert_eq!(geometric_mean(&[]), None);
+        assert_eq!(geometric_mean(&[1.0, 2.0, 3.0, 4.0, 0.0]), None);
+        assert_eq!(geometric_mean(&[1.0, -2.0]), None);
+        assert_eq!(geometric_mean(&[1.0, f32::NAN]), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn log_sum_exp_does_not_overflow() {
+        let lse = log_sum_exp(&[1000.0, 1000.0]);
+        assert!((lse - (1000.0 + 2.0f32.ln())).abs() < 1e-3, "lse {lse}");
+        let data = [0.5f32, -1.0, 2.0, 0.0, 1.5];
+        let naive = data.iter().map(|x| x.exp()).sum::<f32>().ln();
+        assert!((log_sum_exp(&data) - naive).abs() < 1e-5);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn log_sum_exp_edge_cases() {
+        assert_eq!(log_sum_exp(&[]), f32::NEG_INFINITY);
+        assert_eq!(log_sum_exp(&[f32::NEG_INFINITY; 3]), f32::NEG_INFINITY);
+        assert_eq!(log_sum_exp(&[1.0, f32::INFINITY]), f32::INFINITY);
+        assert!(log_sum_exp(&[1.0, f32::NAN]).is_nan());
+        assert!(log_sum_exp(&[f32::NAN, f32::INFINITY]).is_nan());
+        assert!(log_sum_exp(&[f32::NAN, f32::NEG_INFINITY]).is_nan());
+    }
+
+    #[test]
+    fn summarize_matches_individual_stats() {
+        let data = [3.0, -1.5, 4.0, 1.0, 5.5, 9.0, -2.0, 6.0, 5.0, 3.5, 5.0];
+        let summary = summarize(&data).unwrap();
+        let (min, max) = ilp_min_max(&data).unwrap();
+        let (mean, variance) = ilp_mean_variance(&data).unwrap();
+        assert_eq!(
+            summary,
+            Summary {
+                count: data.len(),
+                sum: crate::ilp_sum(&data),
+                min,
+                max,
+                mean,
+                variance,
+            }
+        );
+        assert_eq!(summarize(&[]), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn summary_round_trips_through_json() {
+        let summary = summarize(&[3.0, -1.5, 4.0, 1.0, 5.5]).unwrap();
+        let json = serde_json::to_string(&summary).unwrap();
+        let back: Summary = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, summary);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn summary_with_non_finite_fields_round_trips_through_json() {
+        let summary = summarize(&[1.0, f32::NAN, f32::INFINITY]).unwrap();
+        let json = serde_json::to_string(&summary).unwrap();
+        assert!(json.contains(r#""sum":"NaN""#), "{json}");
+        assert!(json.contains(r#""max":"inf""#), "{json}");
+        let back: Summary = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.count, summary.count);
+        assert!(back.sum.is_nan() && back.mean.is_nan() && back.variance.is_nan());
+        assert_eq!((back.min, back.max), (1.0, f32::INFINITY));
+
+        let summary = Summary {
+            min: f32::NEG_INFINITY,
+            ..summarize(&[2.0]).unwrap()
+        };
+        let back: Summary =
+            serde_json::from_str(&serde_json::to_string(&summary).unwrap()).unwrap();
+        assert_eq!(back, summary);
+        assert!(serde_json::from_str::<Summary>(&json.replace("NaN", "nan")).is_err());
+    }
+}
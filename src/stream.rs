@@ -0,0 +1,134 @@
+//! Incremental reductions for data that arrives in batches.
+
+/// Streaming four-lane summer.
+///
+/// Feeds arbitrary-length batches into four accumulators laid out like those
+/// of [`ilp_sum_n::<4>`](crate::ilp_sum_n). The lane position is carried
+/// across calls, so element `k` of the overall stream always lands in lane
+/// `k % 4` no matter how the stream is split. The result equals
+/// `ilp_sum_n::<4>` of the concatenated batches exactly when the total length
+/// is a multiple of 4, and otherwise up to the rounding of the final partial
+/// chunk, which `ilp_sum_n` adds after merging the lanes rather than into
+/// them. It is not bit-identical to [`ilp_sum`](crate::ilp_sum) for `f32`,
+/// which may run a wider SIMD kernel.
+#[derive(Clone, Debug)]
+pub struct IlpSummer {
+    accs: [f32; 4],
+    idx: usize,
+}
+
+impl IlpSummer {
+    /// Creates a summer with all lanes at zero.
+    pub fn new() -> Self {
+        Self {
+            accs: [0.0; 4],
+            idx: 0,
+        }
+    }
+
+    /// Adds every element of `data` to the running sum.
+    pub fn push_slice(&mut self, data: &[f32]) {
+        // Top up the lanes left open by the previous batch, so the chunked
+        // loop below starts on lane 0.
+        let head_len = ((4 - self.idx) % 4).min(data.len());
+        let (head, rest) = data.split_at(head_len);
+        for &x in head {
+            self.accs[self.idx] += x;
+            self.idx = (self.idx + 1) % 4;
+        }
+
+        let chunks = rest.chunks_exact(4);
+        let remainder = chunks.remainder();
+        for chunk in chunks {
+            self.accs[0] += chunk[0];
+            self.accs[1] += chunk[1];
+            self.accs[2] += chunk[2];
+            self.accs[3] += chunk[3];
+        }
+        for &x in remainder {
+            self.accs[self.idx] += x;
+            self.idx += 1;
+        }
+    }
+
+    /// The sum of everything pushed so far, without consuming the summer;
+    /// equal to what [`finish`](Self::finish) would return now.
+    pub fn partial(&self) -> f32 {
+        self.accs[0] + self.accs[1] + self.accs[2] + self.accs[3]
+    }
+
+    /// Zeroes the lanes and the lane position, so the summer can be reused
+    /// for an unrelated stream as if freshly created.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Merges the lanes into the final sum, `0.0` if nothing was pushed.
+    pub fn finish(self) -> f32 {
+        self.partial()
+    }
+}
+
+impl Default for IlpSummer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ilp_sum_n;
+    use std::vec::Vec;
+
+    /// Non-integer values, so any difference in the order of additions shows
+    /// up in the rounding.
+    fn harmonic(range: core::ops::Range<usize>) -> Vec<f32> {
+        range.map(|i| 1.0 / (i as f32 + 0.37)).collect()
+    }
+
+    #[test]
+    fn ilp_summer_matches_sum_of_concatenation() {
+        let a = harmonic(0..5);
+        let b = harmonic(5..20);
+
+        let mut summer = IlpSummer::new();
+        summer.push_slice(&a);
+        summer.push_slice(&b);
+        assert_eq!(summer.finish(), ilp_sum_n::<4>(&[a, b].concat()));
+    }
+
+    #[test]
+    fn ilp_summer_keeps_lane_alignment_over_tiny_batches() {
+        let data = harmonic(0..24);
+        let mut summer = IlpSummer::default();
+        for batch in data.chunks(3) {
+            summer.push_slice(batch);
+        }
+        summer.push_slice(&[]);
+        assert_eq!(summer.finish(), ilp_sum_n::<4>(&data));
+    }
+
+    #[test]
+    fn ilp_summer_differs_only_in_tail_rounding() {
+        let data = harmonic(0..23);
+        let mut summer = IlpSummer::new();
+        summer.push_slice(&data);
+        let expected = ilp_sum_n::<4>(&data);
+        assert!((summer.finish() - expected).abs() <= 4.0 * f32::EPSILON * expected);
+    }
+
+    #[test]
+    fn ilp_summer_reset_isolates_streams() {
+        let mut summer = IlpSummer::new();
+        summer.push_slice(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(summer.partial(), 15.0);
+        summer.push_slice(&[6.0]);
+        assert_eq!(summer.partial(), 21.0);
+
+        summer.reset();
+        let data = harmonic(0..12);
+        summer.push_slice(&data);
+        assert_eq!(summer.finish(), ilp_sum_n::<4>(&data));
+    }
+}
@@ -0,0 +1,499 @@
+//! In-place feature transforms.
+
+use crate::error::{check_len, PrepError};
+#[cfg(feature = "std")]
+use crate::reduce::{ilp_max, ilp_sum};
+use crate::reduce::{ilp_min_max, ilp_nansum};
+#[cfg(feature = "std")]
+use crate::stats::{ilp_mean_variance, Summary};
+
+/// Replaces every element `x` of `data` with `f(x)`.
+///
+/// The slice is walked four elements at a time with each lane written
+/// independently, so for a cheap `f` the compiler keeps four evaluations in
+/// flight (or vectorises them); the tail is handled one element at a time.
+#[inline]
+pub fn ilp_map_in_place(data: &mut [f32], f: impl Fn(f32) -> f32) {
+    let mut chunks = data.chunks_exact_mut(4);
+    for chunk in &mut chunks {
+        chunk[0] = f(chunk[0]);
+        chunk[1] = f(chunk[1]);
+        chunk[2] = f(chunk[2]);
+        chunk[3] = f(chunk[3]);
+    }
+    for x in chunks.into_remainder() {
+        *x = f(*x);
+    }
+}
+
+/// Writes `f(a[i], b[i])` into `out[i]` for every index, e.g. `|x, y| x - y`
+/// for residuals or `|x, y| x * y` for feature interactions.
+///
+/// Walks the three slices four elements at a time like [`ilp_map_in_place`],
+/// with the tail handled one element at a time.
+///
+/// # Panics
+///
+/// Panics if `a`, `b` and `out` do not all have the same length; see
+/// [`try_ilp_zip_map`].
+#[inline]
+pub fn ilp_zip_map(a: &[f32], b: &[f32], out: &mut [f32], f: impl Fn(f32, f32) -> f32) {
+    try_ilp_zip_map(a, b, out, f).unwrap_or_else(|e| panic!("ilp_zip_map: {e}"))
+}
+
+/// [`ilp_zip_map`] returning [`PrepError::LengthMismatch`] instead of
+/// panicking. `out` is untouched on error.
+#[inline]
+pub fn try_ilp_zip_map(
+    a: &[f32],
+    b: &[f32],
+    out: &mut [f32],
+    f: impl Fn(f32, f32) -> f32,
+) -> Result<(), PrepError> {
+    check_len(a.len(), b.len())?;
+    check_len(a.len(), out.len())?;
+    let a_chunks = a.chunks_exact(4);
+    let b_chunks = b.chunks_exact(4);
+    let a_rem = a_chunks.remainder();
+    let b_rem = b_chunks.remainder();
+    let mut out_chunks = out.chunks_exact_mut(4);
+    for ((xs, ys), os) in a_chunks.zip(b_chunks).zip(&mut out_chunks) {
+        os[0] = f(xs[0], ys[0]);
+        os[1] = f(xs[1], ys[1]);
+        os[2] = f(xs[2], ys[2]);
+        os[3] = f(xs[3], ys[3]);
+    }
+    for ((o, &x), &y) in out_chunks.into_remainder().iter_mut().zip(a_rem).zip(b_rem) {
+        *o = f(x, y);
+    }
+    Ok(())
+}
+
+/// Rewrites `data` as z-scores, `(x - mean) / std`.
+///
+/// The mean and population standard deviation come from one
+/// [`ilp_mean_variance`] pass. If the variance is zero the values are only
+/// centred, so a constant input becomes all zeros rather than NaN or
+/// infinity. Empty input is left untouched.
+#[cfg(feature = "std")]
+pub fn standardize_in_place(data: &mut [f32]) {
+    let Some((mean, variance)) = ilp_mean_variance(data) else {
+        return;
+    };
+    standardize_with(data, mean, variance);
+}
+
+/// Rewrites `data` as z-scores using the mean and variance of a stored
+/// [`Summary`] instead of recomputing them, so new data is scaled exactly like
+/// the data the summary was taken from.
+///
+/// A zero variance only centres the values, as in [`standardize_in_place`].
+#[cfg(feature = "std")]
+pub fn apply_standardization(data: &mut [f32], summary: &Summary) {
+    standardize_with(data, summary.mean, summary.variance);
+}
+
+#[cfg(feature = "std")]
+fn standardize_with(data: &mut [f32], mean: f32, variance: f32) {
+    if variance > 0.0 {
+        let inv_std = 1.0 / variance.sqrt();
+        ilp_map_in_place(data, |x| (x - mean) * inv_std);
+    } else {
+        ilp_map_in_place(data, |x| x - mean);
+    }
+}
+
+/// Rewrites `data` as its softmax, `exp(x) / sum(exp(x))`.
+///
+/// The maximum from [`ilp_max`] is subtracted before exponentiating, so
+/// every exponent is at most zero and large logits cannot overflow. The
+/// exponentials are written through [`ilp_map_in_place`], summed with
+/// [`ilp_sum`], and scaled by the reciprocal of the sum in a second map; the
+/// result sums to one up to rounding. Empty input is left untouched. A NaN,
+/// an infinite maximum, or all elements `-inf` turn the output into NaN.
+#[cfg(feature = "std")]
+pub fn softmax_in_place(data: &mut [f32]) {
+    let Some(max) = ilp_max(data) else {
+        return;
+    };
+    ilp_map_in_place(data, |x| (x - max).exp());
+    let inv_sum = 1.0 / ilp_sum(data);
+    ilp_map_in_place(data, |x| x * inv_sum);
+}
+
+/// Linearly rescales `data` from its own `[min, max]` range into `[lo, hi]`.
+///
+/// The range comes from one [`ilp_min_max`] pass and the rewrite goes through
+/// [`ilp_map_in_place`]. If every element is equal the range is zero and
+/// everything is mapped to `lo`. Empty input is left untouched.
+///
+/// The range and the offsets from `min` are computed in `f64`: in `f32`,
+/// `max - min` overflows to infinity for finite data spanning more than
+/// `f32::MAX`, such as `[-3e38, 3e38]`.
+pub fn min_max_scale_in_place(data: &mut [f32], lo: f32, hi: f32) {
+    let Some((min, max)) = ilp_min_max(data) else {
+        return;
+    };
+    let (min, lo) = (f64::from(min), f64::from(lo));
+    let range = f64::from(max) - min;
+    let scale = if range > 0.0 {
+        (f64::from(hi) - lo) / range
+    } else {
+        0.0
+    };
+    ilp_map_in_place(data, |x| (lo + (f64::from(x) - min) * scale) as f32);
+}
+
+/// Replaces every NaN in `data` with the mean of the non-NaN elements.
+///
+/// The mean comes from one [`ilp_nansum`] pass and the NaN positions are
+/// rewritten through [`ilp_map_in_place`]. If every element is NaN there is
+/// no mean to impute, and the data is left as NaN.
+pub fn impute_mean_in_place(data: &mut [f32]) {
+    let (sum, count) = ilp_nansum(data);
+    if count == 0 {
+        return;
+    }
+    let mean = sum / count as f32;
+    ilp_map_in_place(data, |x| if x.is_nan() { mean } else { x });
+}
+
+/// Clamps every element of `data` into `[lo, hi]` (winsorization to a fixed
+/// range), four lanes at a time through [`ilp_map_in_place`].
+///
+/// NaN elements are left as NaN, matching [`f32::clamp`].
+///
+/// # Panics
+///
+/// Panics if `lo > hi` or either bound is NaN; see [`try_clip_in_place`].
+pub fn clip_in_place(data: &mut [f32], lo: f32, hi: f32) {
+    try_clip_in_place(data, lo, hi).unwrap_or_else(|e| panic!("clip_in_place: {e}"))
+}
+
+/// [`clip_in_place`] returning [`PrepError::InvalidParam`] instead of
+/// panicking. `data` is untouched on error.
+pub fn try_clip_in_place(data: &mut [f32], lo: f32, hi: f32) -> Result<(), PrepError> {
+    if lo > hi || lo.is_nan() || hi.is_nan() {
+        return Err(PrepError::InvalidParam("lo must not exceed hi"));
+    }
+    // Comparisons with NaN are false, so NaN falls through unchanged.
+    ilp_map_in_place(data, |x| {
+        if x < lo {
+            lo
+        } else if x > hi {
+            hi
+        } else {
+            x
+        }
+    });
+    Ok(())
+}
+
+/// [`clip_in_place`] that also returns how many elements were below `lo` and
+/// how many were above `hi`, e.g. to flag a faulty sensor before scaling.
+///
+/// The counts are kept per lane alongside the clamping, so validation costs
+/// no second pass. NaN elements are ignored: they are left as NaN and counted
+/// in neither total.
+///
+/// # Panics
+///
+/// Panics if `lo > hi` or either bound is NaN; see [`try_clip_and_count`].
+pub fn clip_and_count(data: &mut [f32], lo: f32, hi: f32) -> (usize, usize) {
+    try_clip_and_count(data, lo, hi).unwrap_or_else(|e| panic!("clip_and_count: {e}"))
+}
+
+/// [`clip_and_count`] returning [`PrepError::InvalidParam`] instead of
+/// panicking. `data` is untouched on error.
+pub fn try_clip_and_count(data: &mut [f32], lo: f32, hi: f32) -> Result<(usize, usize), PrepError> {
+    if lo > hi || lo.is_nan() || hi.is_nan() {
+        return Err(PrepError::InvalidParam("lo must not exceed hi"));
+    }
+    let mut below = [0usize; 4];
+    let mut above = [0usize; 4];
+    let mut chunks = data.chunks_exact_mut(4);
+    for chunk in &mut chunks {
+        for ((x, b), a) in chunk.iter_mut().zip(&mut below).zip(&mut above) {
+            let (is_below, is_above) = (*x < lo, *x > hi);
+            *b += is_below as usize;
+            *a += is_above as usize;
+            *x = if is_below {
+                lo
+            } else if is_above {
+                hi
+            } else {
+                *x
+            };
+        }
+    }
+    let mut below = below[0] + below[1] + below[2] + below[3];
+    let mut above = above[0] + above[1] + above[2] + above[3];
+    for x in chunks.into_remainder() {
+        if *x < lo {
+            *x = lo;
+            below += 1;
+        } else if *x > hi {
+            *x = hi;
+            above += 1;
+        }
+    }
+    Ok((below, above))
+}
+
+/// SAXPY: `y[i] = a * x[i] + y[i]`.
+///
+/// Every update is a single [`f32::mul_add`], i.e. a fused multiply-add with
+/// one rounding, which compiles to an FMA instruction on targets that have
+/// one (enable it with `target-feature=+fma` or `target-cpu=native`;
+/// otherwise `mul_add` falls back to a slow software routine). The loop is
+/// unrolled four lanes at a time.
+///
+/// # Panics
+///
+/// Panics if `x` and `y` have different lengths; see [`try_ilp_axpy`].
+#[cfg(feature = "std")]
+pub fn ilp_axpy(a: f32, x: &[f32], y: &mut [f32]) {
+    try_ilp_axpy(a, x, y).unwrap_or_else(|e| panic!("ilp_axpy: {e}"))
+}
+
+/// [`ilp_axpy`] returning [`PrepError::LengthMismatch`] instead of
+/// panicking. `y` is untouched on error.
+#[cfg(feature = "std")]
+pub fn try_ilp_axpy(a: f32, x: &[f32], y: &mut [f32]) -> Result<(), PrepError> {
+    check_len(x.len(), y.len())?;
+    let x_chunks = x.chunks_exact(4);
+    let x_rem = x_chunks.remainder();
+    let mut y_chunks = y.chunks_exact_mut(4);
+    for (xs, ys) in x_chunks.zip(&mut y_chunks) {
+        ys[0] = a.mul_add(xs[0], ys[0]);
+        ys[1] = a.mul_add(xs[1], ys[1]);
+        ys[2] = a.mul_add(xs[2], ys[2]);
+        ys[3] = a.mul_add(xs[3], ys[3]);
+    }
+    for (yi, &xi) in y_chunks.into_remainder().iter_mut().zip(x_rem) {
+        *yi = a.mul_add(xi, *yi);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn ilp_map_in_place_matches_plain_loop() {
+        let mut data: Vec<f32> = (0..11).map(|i| i as f32 * 0.25 - 1.0).collect();
+        let mut expected = data.clone();
+        for x in expected.iter_mut() {
+            *x = *x * 2.0 + 1.0;
+        }
+        ilp_map_in_place(&mut data, |x| x * 2.0 + 1.0);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn ilp_zip_map_matches_naive_zip() {
+        let a: Vec<f32> = (0..11).map(|i| i as f32 * 0.5 - 2.0).collect();
+        let b: Vec<f32> = (0..11).map(|i| 3.0 - i as f32).collect();
+        let expected: Vec<f32> = a.iter().zip(&b).map(|(&x, &y)| x * y).collect();
+        let mut out = vec![0.0; 11];
+        ilp_zip_map(&a, &b, &mut out, |x, y| x * y);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn try_ilp_zip_map_reports_length_mismatch() {
+        let mut out = [0.0; 3];
+        assert_eq!(
+            try_ilp_zip_map(&[1.0; 3], &[1.0; 4], &mut out, |x, y| x + y),
+            Err(PrepError::LengthMismatch { a: 3, b: 4 })
+        );
+        assert_eq!(
+            try_ilp_zip_map(&[1.0; 4], &[1.0; 4], &mut out, |x, y| x + y),
+            Err(PrepError::LengthMismatch { a: 4, b: 3 })
+        );
+        assert_eq!(out, [0.0; 3]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn standardize_in_place_yields_zero_mean_unit_std() {
+        let mut data: Vec<f32> = (0..37).map(|i| (i * i) as f32 * 0.3 + 2.0).collect();
+        standardize_in_place(&mut data);
+        let (mean, variance) = ilp_mean_variance(&data).unwrap();
+        assert!(mean.abs() < 1e-5, "mean {mean}");
+        assert!((variance.sqrt() - 1.0).abs() < 1e-5, "variance {variance}");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn apply_standardization_uses_stored_summary() {
+        let summary = crate::stats::summarize(&[1.0, 3.0, 5.0, 7.0]).unwrap();
+        let mut data = [4.0, 9.0, -1.0];
+        apply_standardization(&mut data, &summary);
+        let std = summary.variance.sqrt();
+        for (got, want) in data.iter().zip([0.0, 5.0 / std, -5.0 / std]) {
+            assert!((got - want).abs() < 1e-6, "{got} vs {want}");
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn standardize_in_place_maps_constant_input_to_zeros() {
+        let mut data = [4.5f32; 7];
+        standardize_in_place(&mut data);
+        assert_eq!(data, [0.0; 7]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn softmax_in_place_sums_to_one() {
+        let mut data: Vec<f32> = (0..13).map(|i| i as f32 * 0.8 - 4.0).collect();
+        softmax_in_place(&mut data);
+        assert!((ilp_sum(&data) - 1.0).abs() < 1e-6);
+        assert!(data.windows(2).all(|w| w[0] < w[1]));
+
+        let mut large = [1000.0f32, 1000.0];
+        softmax_in_place(&mut large);
+        assert_eq!(large, [0.5, 0.5]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn softmax_in_place_edge_cases() {
+        let mut data = [0.0f32; 3];
+        softmax_in_place(&mut data);
+        assert!(data.iter().all(|&p| (p - 1.0 / 3.0).abs() < 1e-7));
+
+        let mut empty: [f32; 0] = [];
+        softmax_in_place(&mut empty);
+    }
+
+    #[test]
+    fn min_max_scale_in_place_maps_range_endpoints() {
+        let mut data = [0.0, 5.0, 10.0];
+        min_max_scale_in_place(&mut data, -1.0, 1.0);
+        assert_eq!(data, [-1.0, 0.0, 1.0]);
+
+        let mut data: Vec<f32> = (0..9).map(|i| i as f32 * 4.0 - 8.0).collect();
+        min_max_scale_in_place(&mut data, 0.0, 1.0);
+        let expected: Vec<f32> = (0..9).map(|i| i as f32 / 8.0).collect();
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn min_max_scale_in_place_handles_range_wider_than_f32_max() {
+        let mut data = [-3e38, 0.0, 3e38];
+        min_max_scale_in_place(&mut data, 0.0, 1.0);
+        assert_eq!(data, [0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn min_max_scale_in_place_maps_constant_input_to_lo() {
+        let mut data = [3.0f32; 5];
+        min_max_scale_in_place(&mut data, 2.0, 4.0);
+        assert_eq!(data, [2.0; 5]);
+    }
+
+    #[test]
+    fn impute_mean_in_place_fills_nan_with_mean() {
+        let mut data = [2.0, f32::NAN, 4.0];
+        impute_mean_in_place(&mut data);
+        assert_eq!(data, [2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn impute_mean_in_place_leaves_all_nan_input() {
+        let mut data = [f32::NAN; 3];
+        impute_mean_in_place(&mut data);
+        assert!(data.iter().all(|x| x.is_nan()));
+    }
+
+    #[test]
+    fn clip_in_place_clamps_into_range() {
+        let mut data = [-5.0, 0.0, 5.0];
+        clip_in_place(&mut data, -1.0, 1.0);
+        assert_eq!(data, [-1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn clip_in_place_keeps_nan() {
+        let mut data = [3.0, f32::NAN, -3.0, 0.5, f32::NAN];
+        clip_in_place(&mut data, 0.0, 1.0);
+        assert_eq!(data[0], 1.0);
+        assert!(data[1].is_nan());
+        assert_eq!(data[2], 0.0);
+        assert_eq!(data[3], 0.5);
+        assert!(data[4].is_nan());
+    }
+
+    #[test]
+    #[should_panic(expected = "lo must not exceed hi")]
+    fn clip_in_place_rejects_inverted_range() {
+        clip_in_place(&mut [0.0], 1.0, -1.0);
+    }
+
+    #[test]
+    fn try_clip_in_place_reports_inverted_range() {
+        let mut data = [5.0];
+        assert_eq!(
+            try_clip_in_place(&mut data, 1.0, -1.0),
+            Err(PrepError::InvalidParam("lo must not exceed hi"))
+        );
+        assert_eq!(data, [5.0]);
+    }
+
+    #[test]
+    fn clip_and_count_reports_out_of_range_counts() {
+        let mut data = [-2.0, 0.0, 3.0];
+        assert_eq!(clip_and_count(&mut data, -1.0, 1.0), (1, 1));
+        assert_eq!(data, [-1.0, 0.0, 1.0]);
+
+        let mut data: Vec<f32> = (0..11).map(|i| i as f32 - 5.0).collect();
+        data[2] = f32::NAN;
+        data[9] = f32::NAN;
+        let mut clipped = data.clone();
+        clip_in_place(&mut clipped, -2.0, 3.0);
+        // -5 and -4 are below and 5 is above; the NaNs replaced -3 and 4.
+        assert_eq!(clip_and_count(&mut data, -2.0, 3.0), (2, 1));
+        for (got, want) in data.iter().zip(&clipped) {
+            assert!(got == want || (got.is_nan() && want.is_nan()));
+        }
+    }
+
+    #[test]
+    fn try_clip_and_count_reports_inverted_range() {
+        let mut data = [5.0];
+        assert_eq!(
+            try_clip_and_count(&mut data, 1.0, -1.0),
+            Err(PrepError::InvalidParam("lo must not exceed hi"))
+        );
+        assert_eq!(data, [5.0]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn ilp_axpy_matches_scalar_reference() {
+        let x: Vec<f32> = (0..11).map(|i| i as f32 * 0.3).collect();
+        let mut y: Vec<f32> = (0..11).map(|i| 1.0 - i as f32).collect();
+        let expected: Vec<f32> = x
+            .iter()
+            .zip(&y)
+            .map(|(&xi, &yi)| 2.0f32.mul_add(xi, yi))
+            .collect();
+        ilp_axpy(2.0, &x, &mut y);
+        assert_eq!(y, expected);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn try_ilp_axpy_reports_length_mismatch() {
+        let mut y = [1.0; 3];
+        assert_eq!(
+            try_ilp_axpy(2.0, &[1.0; 4], &mut y),
+            Err(PrepError::LengthMismatch { a: 4, b: 3 })
+        );
+        assert_eq!(y, [1.0; 3]);
+    }
+}
@@ -0,0 +1,23 @@
+//! `ilp_sum` over an `Accumulate` type defined outside the crate.
+
+use ilp_data_prep::{ilp_sum, Accumulate};
+
+/// Saturating 16.16 fixed-point number.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Q16(i32);
+
+impl Accumulate for Q16 {
+    const IDENTITY: Self = Q16(0);
+
+    fn combine(self, other: Self) -> Self {
+        Q16(self.0.saturating_add(other.0))
+    }
+}
+
+#[test]
+fn ilp_sum_accepts_user_defined_accumulate() {
+    let data: Vec<Q16> = (1..=9).map(|i| Q16(i << 16)).collect();
+    assert_eq!(ilp_sum(&data), Q16(45 << 16));
+    assert_eq!(ilp_sum(&[Q16(i32::MAX), Q16(1)]), Q16(i32::MAX));
+    assert_eq!(ilp_sum::<Q16>(&[]), Q16::IDENTITY);
+}
@@ -0,0 +1,203 @@
+//! Every public reduction on inputs of length 0, 1, 3, 4 and 5: empty input,
+//! a tail-only input, exactly one chunk, and one chunk plus a tail. Functions
+//! behind a cargo feature are only checked when it is enabled.
+
+use ilp_data_prep::{
+    approx_quantile, ilp_dot, ilp_max, ilp_mean, ilp_mean_variance, ilp_min, ilp_min_max,
+    ilp_nansum, ilp_product, ilp_reduce, ilp_sum, ilp_sum_aligned, ilp_sum_blocked,
+    ilp_sum_checked, ilp_sum_deterministic, ilp_sum_exact, ilp_sum_iter, ilp_sum_kahan, ilp_sum_n,
+    ilp_sum_pairwise, ilp_sum_sq, ilp_sum_strided, native_sum, summarize, IlpSummer, ReducerConfig,
+};
+
+const LENGTHS: [usize; 5] = [0, 1, 3, 4, 5];
+
+/// `[1.0, 2.0, ..., len]`.
+fn ramp(len: usize) -> Vec<f32> {
+    (1..=len).map(|i| i as f32).collect()
+}
+
+fn triangle(len: usize) -> f32 {
+    (len * (len + 1) / 2) as f32
+}
+
+fn squares(len: usize) -> f32 {
+    (len * (len + 1) * (2 * len + 1) / 6) as f32
+}
+
+#[test]
+fn sums_cover_every_length() {
+    for len in LENGTHS {
+        let data = &ramp(len);
+        let expected = triangle(len);
+        assert_eq!(native_sum(data), expected, "len {len}");
+        assert_eq!(ilp_sum(data), expected, "len {len}");
+        assert_eq!(ilp_sum_deterministic(data), expected, "len {len}");
+        assert_eq!(ilp_sum_checked(data), Some(expected), "len {len}");
+        assert_eq!(ilp_sum_aligned(data), expected, "len {len}");
+        assert_eq!(ilp_sum_n::<1>(data), expected, "len {len}");
+        assert_eq!(ilp_sum_n::<8>(data), expected, "len {len}");
+        assert_eq!(ilp_sum_iter(data.iter().copied()), expected, "len {len}");
+        assert_eq!(ilp_sum_strided(data, 1, 0), expected, "len {len}");
+        assert_eq!(ilp_sum_kahan(data), expected, "len {len}");
+        assert_eq!(ilp_sum_pairwise(data), expected, "len {len}");
+        assert_eq!(ilp_sum_blocked(data, 2), expected, "len {len}");
+        assert_eq!(ilp_nansum(data), (expected, len), "len {len}");
+        assert_eq!(ilp_reduce(data, 0.0, |a, b| a + b), expected, "len {len}");
+        assert_eq!(ReducerConfig::new().reduce_sum(data), expected, "len {len}");
+
+        let mut summer = IlpSummer::new();
+        summer.push_slice(data);
+        assert_eq!(summer.finish(), expected, "len {len}");
+    }
+}
+
+#[test]
+fn sequential_sums_of_empty_input_are_negative_zero() {
+    assert_eq!(native_sum(&[]).to_bits(), (-0.0f32).to_bits());
+    assert_eq!(ilp_sum_deterministic(&[]).to_bits(), (-0.0f32).to_bits());
+}
+
+#[test]
+fn products_and_squares_cover_every_length() {
+    let factorials = [1.0, 1.0, 6.0, 24.0, 120.0];
+    for (i, len) in LENGTHS.into_iter().enumerate() {
+        let data = &ramp(len);
+        assert_eq!(ilp_product(data), factorials[i], "len {len}");
+        assert_eq!(ilp_sum_sq(data), squares(len), "len {len}");
+        assert_eq!(ilp_dot(data, data), squares(len), "len {len}");
+    }
+}
+
+#[test]
+fn exact_sum_covers_chunk_multiples() {
+    assert_eq!(ilp_sum_exact(&[]), 0.0);
+    assert_eq!(ilp_sum_exact(&ramp(4)), 10.0);
+}
+
+#[test]
+fn optional_reductions_are_none_only_when_empty() {
+    for len in LENGTHS {
+        let data = &ramp(len);
+        if len == 0 {
+            assert_eq!(ilp_mean(data), None);
+            assert_eq!(ilp_min(data), None);
+            assert_eq!(ilp_max(data), None);
+            assert_eq!(ilp_min_max(data), None);
+            assert_eq!(ilp_mean_variance(data), None);
+            assert_eq!(summarize(data), None);
+            assert_eq!(approx_quantile(data, 0.5), None);
+            continue;
+        }
+        let n = len as f32;
+        let mean = (n + 1.0) / 2.0;
+        let variance = (n * n - 1.0) / 12.0;
+        assert_eq!(ilp_mean(data), Some(mean), "len {len}");
+        assert_eq!(ilp_min(data), Some(1.0), "len {len}");
+        assert_eq!(ilp_max(data), Some(n), "len {len}");
+        assert_eq!(ilp_min_max(data), Some((1.0, n)), "len {len}");
+
+        let (m, v) = ilp_mean_variance(data).unwrap();
+        assert_eq!(m, mean, "len {len}");
+        assert!((v - variance).abs() < 1e-6, "len {len}: variance {v}");
+
+        let summary = summarize(data).unwrap();
+        assert_eq!(summary.count, len, "len {len}");
+        assert_eq!(summary.sum, triangle(len), "len {len}");
+        assert_eq!((summary.min, summary.max), (1.0, n), "len {len}");
+
+        // Between the two middle elements, give or take one bucket, which
+        // spans 2^-5 of the magnitude of its values.
+        let median = approx_quantile(data, 0.5).unwrap();
+        let (lo, hi) = (data[(len - 1) / 2], data[len / 2]);
+        assert!(
+            median >= lo - lo / 32.0 && median <= hi + hi / 32.0,
+            "len {len}: median {median}"
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn float_math_reductions_cover_every_length() {
+    use ilp_data_prep::{cosine_similarity, geometric_mean, ilp_l2_norm, log_sum_exp};
+
+    for len in LENGTHS {
+        let data = &ramp(len);
+        assert_eq!(ilp_l2_norm(data), squares(len).sqrt(), "len {len}");
+        if len == 0 {
+            assert_eq!(cosine_similarity(data, data), None);
+            assert_eq!(geometric_mean(data), None);
+            assert_eq!(log_sum_exp(data), f32::NEG_INFINITY);
+            continue;
+        }
+        let cos = cosine_similarity(data, data).unwrap();
+        assert!((cos - 1.0).abs() < 1e-6, "len {len}: cosine {cos}");
+
+        let log_factorial: f32 = data.iter().map(|x| x.ln()).sum();
+        let expected = (log_factorial / len as f32).exp();
+        let gm = geometric_mean(data).unwrap();
+        assert!(
+            (gm - expected).abs() < 1e-5,
+            "len {len}: geometric mean {gm}"
+        );
+
+        let expected = data.iter().map(|x| x.exp()).sum::<f32>().ln();
+        let lse = log_sum_exp(data);
+        assert!(
+            (lse - expected).abs() < 1e-5,
+            "len {len}: log-sum-exp {lse}"
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn matrix_reductions_cover_every_length() {
+    use ilp_data_prep::{col_sums, row_sums};
+
+    for len in LENGTHS {
+        let data = ramp(len);
+        let rows: [&[f32]; 2] = [&data, &data];
+        assert_eq!(row_sums(&rows), [triangle(len); 2], "len {len}");
+        let doubled: Vec<f32> = data.iter().map(|x| 2.0 * x).collect();
+        assert_eq!(col_sums(&rows, len), doubled, "len {len}");
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_ilp_sum_covers_every_length() {
+    use ilp_data_prep::par_ilp_sum;
+
+    for len in LENGTHS {
+        assert_eq!(par_ilp_sum(&ramp(len)), triangle(len), "len {len}");
+    }
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn half_sums_cover_every_length() {
+    use half::{bf16, f16};
+    use ilp_data_prep::{ilp_sum_bf16, ilp_sum_f16};
+
+    for len in LENGTHS {
+        let data = ramp(len);
+        let halves: Vec<f16> = data.iter().map(|&x| f16::from_f32(x)).collect();
+        let brains: Vec<bf16> = data.iter().map(|&x| bf16::from_f32(x)).collect();
+        assert_eq!(ilp_sum_f16(&halves), triangle(len), "len {len}");
+        assert_eq!(ilp_sum_bf16(&brains), triangle(len), "len {len}");
+    }
+}
+
+#[cfg(feature = "num-complex")]
+#[test]
+fn ilp_cdot_covers_every_length() {
+    use ilp_data_prep::ilp_cdot;
+    use num_complex::Complex32;
+
+    for len in LENGTHS {
+        let data: Vec<Complex32> = ramp(len).iter().map(|&x| Complex32::new(x, x)).collect();
+        let expected = Complex32::new(2.0 * squares(len), 0.0);
+        assert_eq!(ilp_cdot(&data, &data), expected, "len {len}");
+    }
+}
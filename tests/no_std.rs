@@ -0,0 +1,19 @@
+//! Exercises the `core`-only reductions. Run with
+//! `cargo test --no-default-features --test no_std` so the library itself is
+//! built without `std`.
+
+use ilp_data_prep::{ilp_dot, ilp_map_in_place, ilp_max, ilp_min, ilp_min_max, ilp_sum};
+
+#[test]
+fn core_reductions_work_without_std() {
+    let data = [1.0f32, -2.0, 3.0, 4.5, 0.5, 6.0, -7.0];
+    assert_eq!(ilp_sum(&data), 6.0);
+    assert_eq!(ilp_dot(&data, &[1.0; 7]), 6.0);
+    assert_eq!(ilp_min(&data), Some(-7.0));
+    assert_eq!(ilp_max(&data), Some(6.0));
+    assert_eq!(ilp_min_max(&data), Some((-7.0, 6.0)));
+
+    let mut mapped = data;
+    ilp_map_in_place(&mut mapped, |x| x * 2.0);
+    assert_eq!(ilp_sum(&mapped), 12.0);
+}